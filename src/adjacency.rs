@@ -0,0 +1,43 @@
+use point::Point;
+use scalar::Scalar;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Orientation {
+    Vertical,
+    Horizontal,
+}
+
+/// The wall shared by two touching rectangles, running from `start` to `end` in
+/// increasing coordinate order.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Adjacency<T = f32> {
+    start: Point<T>,
+    end: Point<T>,
+    orientation: Orientation
+}
+
+impl<T: Scalar> Adjacency<T> {
+    pub fn new(start: Point<T>, end: Point<T>, orientation: Orientation) -> Adjacency<T> {
+        Adjacency { start, end, orientation }
+    }
+
+    pub fn start(&self) -> &Point<T> {
+        &self.start
+    }
+
+    pub fn end(&self) -> &Point<T> {
+        &self.end
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn length(&self) -> T {
+        self.start.distance(&self.end)
+    }
+
+    pub fn segment(&self) -> (Point<T>, Point<T>) {
+        (self.start, self.end)
+    }
+}
@@ -1,6 +1,10 @@
 use point::Point;
+use rect::Rect;
+use scalar::Scalar;
 use rand::Rng;
 use dimensionoptions::DimensionOptions;
+use splitstrategy::{SplitStrategy, AlternatingSplit};
+use adjacency::Orientation;
 use std::cmp::Ordering;
 
 #[derive(Clone, Copy)]
@@ -9,128 +13,213 @@ pub enum ChunkSplit {
     Horizontal,
 }
 
-pub struct Chunk {
-    lower_left: Point,
-    upper_right: Point,
-    chunk_split: ChunkSplit
+pub struct Chunk<T = f32> {
+    rect: Rect<T>,
+    chunk_split: ChunkSplit,
+    depth: usize,
+    hallway_budget: T
 }
 
-impl Chunk {
-    pub fn new(lower_left: Point, upper_right: Point, chunk_split: ChunkSplit) -> Chunk {
-        Chunk { lower_left: lower_left, upper_right: upper_right, chunk_split: chunk_split }
+impl<T: Scalar> Chunk<T> {
+    pub fn new(lower_left: Point<T>, upper_right: Point<T>, chunk_split: ChunkSplit) -> Chunk<T> {
+        Chunk { rect: Rect::new(lower_left, upper_right), chunk_split, depth: 0, hallway_budget: T::ZERO }
     }
 
-    pub fn width(&self) -> f32 {
-        self.upper_right.x() - self.lower_left.x()
+    fn child(&self, lower_left: Point<T>, upper_right: Point<T>, chunk_split: ChunkSplit) -> Chunk<T> {
+        Chunk { rect: Rect::new(lower_left, upper_right), chunk_split, depth: self.depth, hallway_budget: T::ZERO }
     }
 
-    pub fn height(&self) -> f32 {
-        self.upper_right.y() - self.lower_left.y()
+    pub fn width(&self) -> T {
+        self.rect.width()
     }
 
-    pub fn area(&self) -> f32 {
-        self.width() * self.height()
+    pub fn height(&self) -> T {
+        self.rect.height()
     }
 
-    fn can_split(&self, dimension_options: &DimensionOptions) -> bool {
-        self.area() > (dimension_options.min_area * 2.0f32) && 
+    pub fn area(&self) -> T {
+        self.rect.area()
+    }
+
+    pub fn can_split(&self, dimension_options: &DimensionOptions<T>) -> bool {
+        self.area() > (dimension_options.min_area + dimension_options.min_area) && 
             (self.can_split_vertically(dimension_options) || self.can_split_horizontally(dimension_options))
     }
 
-    fn can_split_vertically(&self, dimension_options: &DimensionOptions) -> bool {
-        self.width() > (dimension_options.min_width * 2.0f32)
+    fn can_split_vertically(&self, dimension_options: &DimensionOptions<T>) -> bool {
+        self.width() > (dimension_options.min_width + dimension_options.min_width)
+    }
+
+    fn can_split_horizontally(&self, dimension_options: &DimensionOptions<T>) -> bool {
+        self.height() > (dimension_options.min_height + dimension_options.min_height)
     }
 
-    fn can_split_horizontally(&self, dimension_options: &DimensionOptions) -> bool {
-        self.height() > (dimension_options.min_height * 2.0f32)
+    pub fn split<R: Rng>(&mut self, dimension_options: &DimensionOptions<T>, rng: &mut R) -> Option<Chunk<T>> {
+        self.split_with(dimension_options, &AlternatingSplit, rng)
     }
 
-    pub fn split<T: Rng + ?Sized>(&mut self, dimension_options: &DimensionOptions, rng: &mut T) -> Option<Chunk> {
+    /// Splits like `split`, with `split_strategy` choosing the direction and place of the
+    /// cut. A chunk more stretched than `max_aspect` allows is still cut across its long side.
+    pub fn split_with<R: Rng>(&mut self, dimension_options: &DimensionOptions<T>, split_strategy: &dyn SplitStrategy<T>, rng: &mut R) -> Option<Chunk<T>> {
         if !self.can_split(dimension_options) {
             return None;
         }
-        match dimension_options.max_area {
-            Some(max_area) => {
-                if self.area() < max_area && rng.gen_weighted_bool(4) {
-                    return None;
-                }
+        if let Some(max_area) = dimension_options.max_area {
+            if self.area() < max_area && !self.is_too_long(dimension_options) && rng.gen::<f32>() < dimension_options.stop_chance {
+                return None;
+            }
+        }
+        let vertical = self.can_split_vertically(dimension_options);
+        let horizontal = self.can_split_horizontally(dimension_options);
+        let split_horizontal = if self.is_too_long(dimension_options) && self.height() > self.width() && horizontal {
+            true
+        } else if self.is_too_long(dimension_options) && self.width() > self.height() && vertical {
+            false
+        } else {
+            let previous = match self.chunk_split {
+                ChunkSplit::Horizontal => Orientation::Horizontal,
+                ChunkSplit::Vertical => Orientation::Vertical
+            };
+            match split_strategy.orientation(&self.rect, previous, vertical, horizontal, rng) {
+                Orientation::Horizontal => horizontal,
+                Orientation::Vertical => !vertical
             }
-            _ => {}
-        };
-        let split_horizontal = match self.chunk_split {
-            ChunkSplit::Horizontal => !self.can_split_vertically(dimension_options),
-            ChunkSplit::Vertical => self.can_split_horizontally(dimension_options)
         };
         if !split_horizontal {
-            let mut min = self.lower_left.x() + dimension_options.min_width;
-            if self.width() > (dimension_options.min_width * 2.0f32) {
-                min = min + 1.0f32;
+            let min_width = self.min_piece(dimension_options.min_width, self.width(), self.height(), dimension_options);
+            let mut min = self.lower_left().x() + min_width;
+            if self.width() > (min_width + min_width) {
+                min += T::ONE;
             }
-            let max = self.upper_right.x() - dimension_options.min_width + 1.0f32;
-            let mut split_x = min;
-            if min < max {
-                split_x = rng.gen_range(min, max);
-            } else if min > max {
+            let max = self.upper_right().x() - min_width + T::ONE;
+            if min > max {
                 panic!("Min is greater than max");
             }
-            let upper_right = self.upper_right.clone();
-            let lower_left = Point::new(split_x, self.lower_left.y());
-            self.upper_right.set_x(split_x);
-            self.chunk_split = ChunkSplit::Vertical;
-            Some(Chunk::new(lower_left, upper_right, ChunkSplit::Vertical))
+            let split_x = split_strategy.position(min, max, rng);
+            Some(self.split_at(ChunkSplit::Vertical, split_x))
         } else {
-            let mut min = self.lower_left.y() + dimension_options.min_height;
-            if self.height() > (dimension_options.min_height * 2.0f32) {
-                min = min + 1.0f32;
+            let min_height = self.min_piece(dimension_options.min_height, self.height(), self.width(), dimension_options);
+            let mut min = self.lower_left().y() + min_height;
+            if self.height() > (min_height + min_height) {
+                min += T::ONE;
             }
-            let max = self.upper_right.y() - dimension_options.min_height + 1.0f32;            
-            let mut split_y = min;
-            if min < max {
-                split_y = rng.gen_range(min, max);
-            } else if min > max {
+            let max = self.upper_right().y() - min_height + T::ONE;
+            if min > max {
                 panic!("Min is greater than max");
             }
+            let split_y = split_strategy.position(min, max, rng);
+            Some(self.split_at(ChunkSplit::Horizontal, split_y))
+        }
+    }
 
-            let upper_right = self.upper_right.clone();
-            let lower_left = Point::new(self.lower_left.x(), split_y);
-            self.upper_right.set_y(split_y);
-            self.chunk_split = ChunkSplit::Horizontal;
-            Some(Chunk::new(lower_left, upper_right, ChunkSplit::Horizontal))
+    /// True when the chunk is more stretched than `max_aspect` allows.
+    pub fn is_too_long(&self, dimension_options: &DimensionOptions<T>) -> bool {
+        dimension_options.max_aspect.is_some_and(|max_aspect| self.rect.aspect_ratio() > max_aspect as f64)
+    }
+
+    /// Smallest length a piece may have along the cut side so that neither piece ends up
+    /// more stretched than `max_aspect`, falling back to `min_side` when that cannot be met.
+    fn min_piece(&self, min_side: T, length: T, across: T, dimension_options: &DimensionOptions<T>) -> T {
+        let min_piece = match dimension_options.max_aspect {
+            Some(max_aspect) => min_side.max(T::from_f64(across.to_f64() / max_aspect as f64)),
+            None => min_side
+        };
+        if min_piece + min_piece > length {
+            min_side
+        } else {
+            min_piece
         }
     }
 
-    pub fn lower_left(&self) -> &Point {
-        &self.lower_left
+    /// Cuts the chunk along a vertical line at x = `at` or a horizontal one at y = `at`,
+    /// keeping the lower or left part and returning the other. The hallway budget is
+    /// shared between the parts by area.
+    pub fn split_at(&mut self, chunk_split: ChunkSplit, at: T) -> Chunk<T> {
+        let upper_right = *self.upper_right();
+        let (lower_left, corner) = match chunk_split {
+            ChunkSplit::Vertical => (Point::new(at, self.lower_left().y()), Point::new(at, upper_right.y())),
+            ChunkSplit::Horizontal => (Point::new(self.lower_left().x(), at), Point::new(upper_right.x(), at))
+        };
+        let budget = self.hallway_budget;
+        self.rect = Rect::new(*self.lower_left(), corner);
+        self.chunk_split = chunk_split;
+        self.depth += 1;
+        let mut child = self.child(lower_left, upper_right, chunk_split);
+        share_hallway_budget(self, &mut child, budget);
+        child
+    }
+
+    pub fn rect(&self) -> &Rect<T> {
+        &self.rect
+    }
+
+    pub fn lower_left(&self) -> &Point<T> {
+        self.rect.lower_left()
     }
 
-    pub fn upper_right(&self) -> &Point {
-        &self.upper_right
+    pub fn upper_right(&self) -> &Point<T> {
+        self.rect.upper_right()
     }
 
     pub fn chunk_split(&self) -> ChunkSplit {
         self.chunk_split
     }
 
-    pub fn strip_hallway(&mut self, side: ChunkSplit, hallway_width: f32) -> Chunk {
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Hallway area this chunk and the chunks cut from it may still use.
+    pub fn hallway_budget(&self) -> T {
+        self.hallway_budget
+    }
+
+    pub fn set_hallway_budget(&mut self, hallway_budget: T) {
+        self.hallway_budget = hallway_budget;
+    }
+
+    pub fn strip_hallway(&mut self, side: ChunkSplit, hallway_width: T) -> Chunk<T> {
         match side {
             ChunkSplit::Horizontal => {
-                let lower_left = Point::new(self.lower_left.x(), self.upper_right.y() - hallway_width);
-                let upper_right = Point::new(self.upper_right.x(), self.upper_right.y());
-                self.upper_right.add(Point::new(0f32, -hallway_width));
+                let lower_left = Point::new(self.lower_left().x(), self.upper_right().y() - hallway_width);
+                let upper_right = *self.upper_right();
+                self.rect = Rect::new(*self.lower_left(), upper_right - Point::new(T::ZERO, hallway_width));
                 Chunk::new(lower_left, upper_right, ChunkSplit::Horizontal)
             },
             ChunkSplit::Vertical => {
-                let lower_left = Point::new(self.upper_right.x() - hallway_width, self.lower_left.y());
-                let upper_right = Point::new(self.upper_right.x(), self.upper_right.y());
-                self.upper_right.add(Point::new(-hallway_width, 0f32));
+                let lower_left = Point::new(self.upper_right().x() - hallway_width, self.lower_left().y());
+                let upper_right = *self.upper_right();
+                self.rect = Rect::new(*self.lower_left(), upper_right - Point::new(hallway_width, T::ZERO));
                 Chunk::new(lower_left, upper_right, ChunkSplit::Vertical)
             },
         }
     }
+
+    /// Strips a hallway along the line between this chunk and `other`, the piece split off
+    /// above or to the right of it, taking `before` off this chunk and `after` off `other`.
+    pub fn strip_hallway_between(&mut self, other: &mut Chunk<T>, side: ChunkSplit, before: T, after: T) -> Chunk<T> {
+        let mut hallway = self.strip_hallway(side, before);
+        let lower_left = *other.lower_left();
+        let (upper_right, start) = match side {
+            ChunkSplit::Horizontal => (Point::new(other.upper_right().x(), lower_left.y() + after), Point::new(lower_left.x(), lower_left.y() + after)),
+            ChunkSplit::Vertical => (Point::new(lower_left.x() + after, other.upper_right().y()), Point::new(lower_left.x() + after, lower_left.y()))
+        };
+        other.rect = Rect::new(start, *other.upper_right());
+        hallway.rect = hallway.rect.union(&Rect::new(lower_left, upper_right));
+        hallway
+    }
+}
+
+/// Gives two neighbouring chunks `budget` between them in proportion to their areas.
+pub fn share_hallway_budget<T: Scalar>(a: &mut Chunk<T>, b: &mut Chunk<T>, budget: T) {
+    let total = a.area().to_f64() + b.area().to_f64();
+    let share = if total > 0f64 { T::from_f64(budget.to_f64() * a.area().to_f64() / total) } else { T::ZERO };
+    a.hallway_budget = share;
+    b.hallway_budget = budget - share;
 }
 
-impl Ord for Chunk {
-    fn cmp(&self, other: &Chunk) -> Ordering {
+impl<T: Scalar> Ord for Chunk<T> {
+    fn cmp(&self, other: &Chunk<T>) -> Ordering {
         if self.area() < other.area() {
             return Ordering::Less;
         } else if self.area() > other.area() {
@@ -141,23 +230,19 @@ impl Ord for Chunk {
     }
 }
 
-impl PartialOrd for Chunk {
-    fn partial_cmp(&self, other: &Chunk) -> Option<Ordering> {
+impl<T: Scalar> PartialOrd for Chunk<T> {
+    fn partial_cmp(&self, other: &Chunk<T>) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl Eq for Chunk {
+impl<T: Scalar> Eq for Chunk<T> {
 }
 
-impl PartialEq for Chunk {
-    fn eq(&self, other: &Chunk) -> bool {
+impl<T: Scalar> PartialEq for Chunk<T> {
+    fn eq(&self, other: &Chunk<T>) -> bool {
         self.cmp(other) == Ordering::Equal
     }
-
-    fn ne(&self, other: &Chunk) -> bool {
-        !self.eq(other)
-    }
 }
 
 
@@ -173,7 +258,7 @@ mod tests {
 
     impl Rng for MockRng {
         fn next_u32(&mut self) -> u32 {
-            return 2u32;
+            0u32
         }
     }
 
@@ -221,16 +306,15 @@ mod tests {
         let mut mockrng = MockRng;
         let new_chunk_option = chunk.split(&DimensionOptions::new(5f32,5f32,5f32), &mut mockrng); 
         let new_chunk = new_chunk_option.unwrap();
-        assert!(match new_chunk.chunk_split() {
-            ChunkSplit::Horizontal => true,
-            _ => false
-        });
+        assert!(matches!(new_chunk.chunk_split(), ChunkSplit::Horizontal));
         assert_eq!(20f32, chunk.upper_right().x());
         assert_eq!(6f32, chunk.upper_right().y());
         assert_eq!(0f32, new_chunk.lower_left().x());
         assert_eq!(6f32, new_chunk.lower_left().y());
         assert_eq!(20f32, new_chunk.upper_right().x());
         assert_eq!(20f32, new_chunk.upper_right().y());
+        assert_eq!(1, chunk.depth());
+        assert_eq!(1, new_chunk.depth());
     }
 
     #[test]
@@ -241,10 +325,7 @@ mod tests {
         let mut mockrng = MockRng;
         let new_chunk_option = chunk.split(&DimensionOptions::new(5f32,5f32,5f32), &mut mockrng); 
         let new_chunk = new_chunk_option.unwrap();
-        assert!(match new_chunk.chunk_split() {
-            ChunkSplit::Horizontal => true,
-            _ => false
-        });
+        assert!(matches!(new_chunk.chunk_split(), ChunkSplit::Horizontal));
         assert_eq!(2f32, chunk.upper_right().x());
         assert_eq!(6f32, chunk.upper_right().y());
         assert_eq!(0f32, new_chunk.lower_left().x());
@@ -261,10 +342,7 @@ mod tests {
         let mut mockrng = MockRng;
         let new_chunk_option = chunk.split(&DimensionOptions::new(5f32,5f32,5f32), &mut mockrng); 
         let new_chunk = new_chunk_option.unwrap();
-        assert!(match new_chunk.chunk_split() {
-            ChunkSplit::Vertical => true,
-            _ => false
-        });
+        assert!(matches!(new_chunk.chunk_split(), ChunkSplit::Vertical));
         assert_eq!(6f32, chunk.upper_right().x());
         assert_eq!(2f32, chunk.upper_right().y());
         assert_eq!(6f32, new_chunk.lower_left().x());
@@ -273,6 +351,19 @@ mod tests {
         assert_eq!(2f32, new_chunk.upper_right().y());
     }
 
+    #[test]
+    fn test_aspect_limited_split() {
+        let mut chunk = Chunk::new(Point::new(0f32, 0f32), Point::new(100f32, 30f32), ChunkSplit::Vertical);
+        let mut dimension_options = DimensionOptions::new(5f32,5f32,5f32);
+        dimension_options.max_aspect = Some(2f32);
+        assert!(chunk.is_too_long(&dimension_options));
+        let new_chunk = chunk.split(&dimension_options, &mut MockRng).unwrap();
+        assert!(matches!(new_chunk.chunk_split(), ChunkSplit::Vertical));
+        assert_eq!(16f32, chunk.upper_right().x());
+        assert_eq!(30f32, chunk.upper_right().y());
+        assert_eq!(16f32, new_chunk.lower_left().x());
+    }
+
     #[test]
     fn test_cant_split() {
         let lower_left = Point::new(0f32, 0f32);
@@ -290,8 +381,8 @@ mod tests {
         let upper_right = Point::new(20f32, 20f32);
         let mut chunk = Chunk::new(lower_left, upper_right, ChunkSplit::Horizontal); 
         let hallway = chunk.strip_hallway(ChunkSplit::Horizontal, 1f32);
-        assert_eq!(19f32, chunk.upper_right.y());
-        assert_eq!(20f32, chunk.upper_right.x());
+        assert_eq!(19f32, chunk.upper_right().y());
+        assert_eq!(20f32, chunk.upper_right().x());
         assert_eq!(19f32, hallway.lower_left().y());
         assert_eq!(0f32, hallway.lower_left().x());
         assert_eq!(20f32, hallway.upper_right().y());
@@ -303,10 +394,31 @@ mod tests {
         let upper_right = Point::new(20f32, 20f32);
         let mut chunk = Chunk::new(lower_left, upper_right, ChunkSplit::Horizontal); 
         let hallway = chunk.strip_hallway(ChunkSplit::Vertical, 1f32);
-        assert_eq!(20f32, chunk.upper_right.y());
-        assert_eq!(19f32, chunk.upper_right.x());
+        assert_eq!(20f32, chunk.upper_right().y());
+        assert_eq!(19f32, chunk.upper_right().x());
         assert_eq!(0f32, hallway.lower_left().y());
         assert_eq!(19f32, hallway.lower_left().x());
         assert_eq!(20f32, hallway.upper_right().y());
     }
+
+    #[test]
+    fn test_integer_split() {
+        let mut chunk = Chunk::new(Point::new(0i32, 0i32), Point::new(20i32, 20i32), ChunkSplit::Vertical);
+        let new_chunk = chunk.split(&DimensionOptions::new(5i32, 5i32, 5i32), &mut MockRng).unwrap();
+        assert_eq!(6, chunk.upper_right().y());
+        assert_eq!(6, new_chunk.lower_left().y());
+        let hallway = chunk.strip_hallway(ChunkSplit::Horizontal, 2);
+        assert_eq!(4, chunk.upper_right().y());
+        assert_eq!(40, hallway.area());
+    }
+
+    #[test]
+    fn test_strip_centered_hallway() {
+        let mut chunk = Chunk::new(Point::new(0i32, 0i32), Point::new(20i32, 20i32), ChunkSplit::Horizontal);
+        let mut new_chunk = chunk.split_at(ChunkSplit::Vertical, 8);
+        let hallway = chunk.strip_hallway_between(&mut new_chunk, ChunkSplit::Vertical, 2, 3);
+        assert_eq!(Rect::new(Point::new(0i32, 0i32), Point::new(6i32, 20i32)), *chunk.rect());
+        assert_eq!(Rect::new(Point::new(6i32, 0i32), Point::new(11i32, 20i32)), *hallway.rect());
+        assert_eq!(Rect::new(Point::new(11i32, 0i32), Point::new(20i32, 20i32)), *new_chunk.rect());
+    }
 }
@@ -0,0 +1,89 @@
+use dungeon::Dungeon;
+use door::{Door, DoorKind, Region};
+use connectivityoptions::ConnectivityOptions;
+use roomgraph::{self, RoomGraph};
+use scalar::Scalar;
+use segment;
+use rand::Rng;
+
+pub struct ConnectivityBuilder<'a, T: 'a> {
+    connectivity_options: &'a ConnectivityOptions<T>
+}
+
+impl<'a, T: Scalar> ConnectivityBuilder<'a, T> {
+    pub fn new(connectivity_options: &'a ConnectivityOptions<T>) -> ConnectivityBuilder<'a, T> {
+        ConnectivityBuilder { connectivity_options }
+    }
+
+    /// Adds doors between neighbouring rooms of different components until every room
+    /// that shares a wall with another is reachable. Returns the number of doors added.
+    pub fn connect<R: Rng>(&self, dungeon: &mut Dungeon<T>, rng: &mut R) -> usize {
+        let graph = RoomGraph::new(dungeon.rooms().len(), dungeon.hallways().len(), dungeon.doors());
+        let labels = graph.component_labels();
+        let component_count = labels.iter().max().map_or(0, |max| max + 1);
+        if component_count <= 1 {
+            return 0;
+        }
+        let mut roots : Vec<usize> = (0..component_count).collect();
+        let mut walls = roomgraph::shared_walls(dungeon.rooms());
+        rng.shuffle(&mut walls);
+        let mut added = 0usize;
+        for (a, b, wall) in walls {
+            let a_root = find(&mut roots, labels[graph.node(Region::Room(a))]);
+            let b_root = find(&mut roots, labels[graph.node(Region::Room(b))]);
+            if a_root == b_root {
+                continue;
+            }
+            roots[a_root] = b_root;
+            let opening = segment::centered(&wall, self.connectivity_options.door_width);
+            dungeon.add_door(Door::new(Region::Room(a), Region::Room(b), opening.0, opening.1, DoorKind::Connectivity));
+            added += 1;
+        }
+        added
+    }
+}
+
+fn find(roots: &mut [usize], label: usize) -> usize {
+    let mut root = label;
+    while roots[root] != root {
+        roots[root] = roots[roots[root]];
+        root = roots[root];
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use room::{Room, RoomId};
+    use dungeon::Dungeon;
+    use door::{Door, DoorKind, Region};
+    use connectivityoptions::ConnectivityOptions;
+    use chunk::{Chunk, ChunkSplit};
+    use rand::thread_rng;
+
+    #[test]
+    fn test_connect() {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0f32, 0f32), Point::new(10f32, 10f32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(10f32, 0f32), Point::new(20f32, 10f32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0f32, 10f32), Point::new(10f32, 20f32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(10f32, 10f32), Point::new(20f32, 20f32), ChunkSplit::Vertical)));
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(0)), Region::Room(RoomId::new(2)), Point::new(4f32, 10f32), Point::new(6f32, 10f32), DoorKind::Loop));
+        assert!(!dungeon.is_connected());
+        assert_eq!(3, dungeon.components().len());
+        let added = ConnectivityBuilder::new(&ConnectivityOptions::new(2f32)).connect(&mut dungeon, &mut thread_rng());
+        assert_eq!(2, added);
+        assert!(dungeon.is_connected());
+    }
+
+    #[test]
+    fn test_already_connected() {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0f32, 0f32), Point::new(10f32, 10f32), ChunkSplit::Vertical)));
+        let added = ConnectivityBuilder::new(&ConnectivityOptions::new(2f32)).connect(&mut dungeon, &mut thread_rng());
+        assert_eq!(0, added);
+        assert!(dungeon.is_connected());
+    }
+}
@@ -0,0 +1,13 @@
+use scalar::Scalar;
+
+pub struct ConnectivityOptions<T = f32> {
+    pub door_width: T,
+}
+
+impl<T: Scalar> ConnectivityOptions<T> {
+    pub fn new(door_width: T) -> ConnectivityOptions<T> {
+        ConnectivityOptions { 
+            door_width 
+        }
+    }
+}
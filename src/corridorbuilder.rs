@@ -0,0 +1,623 @@
+use dungeon::Dungeon;
+use door::{Door, DoorKind, Region};
+use room::RoomId;
+use hallway::Hallway;
+use corridoroptions::{CorridorOptions, CorridorStyle};
+use roomgraph::RoomGraph;
+use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+use segment;
+use rand::Rng;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+const STEPS: [(i64, i64); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const NOISE_SPACING: usize = 4;
+
+/// A cell a corridor can leave by a straight stub into a room. `step` points from the
+/// cell towards the room and `wall` is the coordinate of the room's wall along it.
+struct Entry<T> {
+    cell: usize,
+    room: usize,
+    step: usize,
+    wall: T,
+    extension: Rect<T>
+}
+
+/// Square cells twice the corridor width across. Corridors run through cell centers, so
+/// two corridors in neighbouring cells always keep a corridor width apart.
+struct Grid<T> {
+    origin: Point<T>,
+    width: T,
+    columns: usize,
+    rows: usize,
+    free: Vec<bool>
+}
+
+impl<T: Scalar> Grid<T> {
+    fn new(bounds: &Rect<T>, width: T, obstacles: &[Rect<T>]) -> Grid<T> {
+        let size = (width + width).to_f64();
+        let columns = (bounds.width().to_f64() / size).ceil().max(1f64) as usize;
+        let rows = (bounds.height().to_f64() / size).ceil().max(1f64) as usize;
+        let mut grid = Grid { origin: *bounds.lower_left(), width, columns, rows, free: vec![true; columns * rows] };
+        for obstacle in obstacles {
+            let (low_column, high_column) = grid.span(obstacle.lower_left().x(), obstacle.upper_right().x(), grid.origin.x(), columns);
+            let (low_row, high_row) = grid.span(obstacle.lower_left().y(), obstacle.upper_right().y(), grid.origin.y(), rows);
+            for row in low_row..high_row {
+                for column in low_column..high_column {
+                    let cell = row * columns + column;
+                    if grid.rect(cell).intersects(obstacle) {
+                        grid.free[cell] = false;
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    /// The range of cell indices along one axis that may overlap `low` to `high`.
+    fn span(&self, low: T, high: T, origin: T, count: usize) -> (usize, usize) {
+        let size = (self.width + self.width).to_f64();
+        let first = ((low - origin).to_f64() / size).floor().max(0f64) as usize;
+        let last = (((high - origin).to_f64() / size).ceil().max(0f64) as usize).min(count);
+        (first.min(count), last)
+    }
+
+    fn coordinates(&self, cell: usize) -> (i64, i64) {
+        ((cell % self.columns) as i64, (cell / self.columns) as i64)
+    }
+
+    fn cell(&self, column: i64, row: i64) -> Option<usize> {
+        if column < 0 || row < 0 || column >= self.columns as i64 || row >= self.rows as i64 {
+            None
+        } else {
+            Some(row as usize * self.columns + column as usize)
+        }
+    }
+
+    fn neighbour(&self, cell: usize, step: (i64, i64)) -> Option<usize> {
+        let (column, row) = self.coordinates(cell);
+        self.cell(column + step.0, row + step.1)
+    }
+
+    fn lower_left(&self, cell: usize) -> Point<T> {
+        let (column, row) = self.coordinates(cell);
+        let size = self.width + self.width;
+        self.origin + Point::new(T::from_f64(column as f64) * size, T::from_f64(row as f64) * size)
+    }
+
+    fn rect(&self, cell: usize) -> Rect<T> {
+        let lower_left = self.lower_left(cell);
+        let size = self.width + self.width;
+        Rect::new(lower_left, lower_left + Point::new(size, size))
+    }
+
+    fn center(&self, cell: usize) -> Point<T> {
+        self.lower_left(cell) + Point::new(self.width, self.width)
+    }
+}
+
+/// Cost of a cell in the cheapest route search, cheapest first.
+struct Candidate {
+    cell: usize,
+    cost: f64
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Eq for Candidate {
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Candidate) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+pub struct CorridorBuilder<'a, T: 'a> {
+    corridor_options: &'a CorridorOptions<T>
+}
+
+impl<'a, T: Scalar> CorridorBuilder<'a, T> {
+    pub fn new(corridor_options: &'a CorridorOptions<T>) -> CorridorBuilder<'a, T> {
+        CorridorBuilder { corridor_options }
+    }
+
+    /// Runs a corridor from each part of the dungeon that no door reaches to another part,
+    /// through space free of rooms, hallways and `keep_out`, until every part is joined or
+    /// no free route is left. Returns the number of corridors added.
+    pub fn connect<R: Rng>(&self, dungeon: &mut Dungeon<T>, keep_out: &[Rect<T>], rng: &mut R) -> usize {
+        if dungeon.rooms().len() < 2 {
+            return 0;
+        }
+        let mut obstacles : Vec<Rect<T>> = dungeon.rooms().iter().map(|room| *room.rect()).collect();
+        obstacles.extend(dungeon.hallways().iter().flat_map(|hallway| hallway.rects()));
+        let margin = self.corridor_options.margin;
+        let bounds = obstacles.iter().fold(obstacles[0], |bounds, rect| bounds.union(rect));
+        let bounds = Rect::new(*bounds.lower_left() - Point::new(margin, margin), *bounds.upper_right() + Point::new(margin, margin));
+        obstacles.extend(keep_out.iter().cloned());
+        let mut grid = Grid::new(&bounds, self.corridor_options.width, &obstacles);
+        let mut entries = self.entries(&grid, dungeon, &obstacles);
+        let noise = self.noise(&grid, rng);
+
+        let mut added = 0usize;
+        let mut stuck : Vec<usize> = Vec::new();
+        loop {
+            let graph = RoomGraph::new(dungeon.rooms().len(), dungeon.hallways().len(), dungeon.doors());
+            let labels = graph.component_labels();
+            let room_labels : Vec<usize> = (0..dungeon.rooms().len()).map(|room| labels[graph.node(Region::Room(RoomId::new(room)))]).collect();
+            if room_labels.iter().all(|&label| label == room_labels[0]) {
+                break;
+            }
+            let source = match room_labels.iter().position(|label| !stuck.iter().any(|&room| room_labels[room] == *label)) {
+                Some(room) => room_labels[room],
+                None => break
+            };
+            let path = match self.corridor_options.style {
+                CorridorStyle::Noise => self.cheapest_route(&grid, &entries, &room_labels, source, &noise),
+                CorridorStyle::RandomWalk => self.random_walk(&grid, &entries, &room_labels, source, rng)
+                    .or_else(|| self.cheapest_route(&grid, &entries, &room_labels, source, &noise))
+            };
+            let (cells, start, end) = match path {
+                Some(path) => path,
+                None => {
+                    stuck.push(room_labels.iter().position(|&label| label == source).expect("source component has no room"));
+                    continue;
+                }
+            };
+            let (hallway, rects) = self.corridor(&grid, &cells, &entries[start], &entries[end]);
+            let hallway_idx = dungeon.add_hallway(hallway);
+            for entry in &[&entries[start], &entries[end]] {
+                let (door_start, door_end) = self.opening(&grid, entry);
+                dungeon.add_door(Door::new(Region::Room(RoomId::new(entry.room)), Region::Hallway(hallway_idx), door_start, door_end, DoorKind::Corridor));
+            }
+            for &cell in &cells {
+                grid.free[cell] = false;
+            }
+            entries.retain(|entry| grid.free[entry.cell] && !rects.iter().any(|rect| rect.intersects(&entry.extension)));
+            added += 1;
+        }
+        added
+    }
+
+    /// Every free cell with a clear straight line into a room.
+    fn entries(&self, grid: &Grid<T>, dungeon: &Dungeon<T>, obstacles: &[Rect<T>]) -> Vec<Entry<T>> {
+        let width = self.corridor_options.width;
+        let half = width / (T::ONE + T::ONE);
+        let size = (width + width).to_f64();
+        let mut entries = Vec::new();
+        for (room, rect) in dungeon.rooms().iter().map(|room| *room.rect()).enumerate() {
+            for (step, &(dx, dy)) in STEPS.iter().enumerate() {
+                let horizontal = dx != 0;
+                let wall = match (dx, dy) {
+                    (1, _) => rect.lower_left().x(),
+                    (-1, _) => rect.upper_right().x(),
+                    (_, 1) => rect.lower_left().y(),
+                    _ => rect.upper_right().y()
+                };
+                let (origin_along, origin_across, low, high, count) = if horizontal {
+                    (grid.origin.x(), grid.origin.y(), rect.lower_left().y(), rect.upper_right().y(), grid.rows)
+                } else {
+                    (grid.origin.y(), grid.origin.x(), rect.lower_left().x(), rect.upper_right().x(), grid.columns)
+                };
+                let offset = (wall - origin_along).to_f64() / size;
+                let along = if dx + dy > 0 { offset.floor() as i64 - 1 } else { offset.ceil() as i64 };
+                let (first, last) = grid.span(low, high, origin_across, count);
+                for across in first..last {
+                    let cell = match if horizontal { grid.cell(along, across as i64) } else { grid.cell(across as i64, along) } {
+                        Some(cell) if grid.free[cell] => cell,
+                        _ => continue
+                    };
+                    let center = grid.center(cell);
+                    let (center_along, center_across) = if horizontal { (center.x(), center.y()) } else { (center.y(), center.x()) };
+                    let band_low = center_across - half;
+                    if band_low < low || band_low + width > high {
+                        continue;
+                    }
+                    let extension = if horizontal {
+                        Rect::new(Point::new(center_along, band_low), Point::new(wall, band_low + width))
+                    } else {
+                        Rect::new(Point::new(band_low, center_along), Point::new(band_low + width, wall))
+                    };
+                    if obstacles.iter().any(|obstacle| obstacle.intersects(&extension)) {
+                        continue;
+                    }
+                    entries.push(Entry { cell, room, step, wall, extension });
+                }
+            }
+        }
+        entries
+    }
+
+    /// Smooth random values between 0 and 1 for every cell, interpolated from a coarser
+    /// lattice so that cheap and expensive ground comes in patches.
+    fn noise<R: Rng>(&self, grid: &Grid<T>, rng: &mut R) -> Vec<f64> {
+        let lattice_columns = grid.columns / NOISE_SPACING + 2;
+        let lattice_rows = grid.rows / NOISE_SPACING + 2;
+        let lattice : Vec<f64> = (0..lattice_columns * lattice_rows).map(|_| rng.gen::<f64>()).collect();
+        (0..grid.free.len()).map(|cell| {
+            let (column, row) = grid.coordinates(cell);
+            let (x, y) = (column as f64 / NOISE_SPACING as f64, row as f64 / NOISE_SPACING as f64);
+            let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+            let (tx, ty) = (x - x0 as f64, y - y0 as f64);
+            let at = |column: usize, row: usize| lattice[row * lattice_columns + column];
+            let bottom = at(x0, y0) * (1f64 - tx) + at(x0 + 1, y0) * tx;
+            let top = at(x0, y0 + 1) * (1f64 - tx) + at(x0 + 1, y0 + 1) * tx;
+            bottom * (1f64 - ty) + top * ty
+        }).collect()
+    }
+
+    /// Entries by cell, split into those of the `source` component and the others.
+    fn ends(&self, grid: &Grid<T>, entries: &[Entry<T>], room_labels: &[usize], source: usize) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+        let mut starts = vec![None; grid.free.len()];
+        let mut goals = vec![None; grid.free.len()];
+        for (idx, entry) in entries.iter().enumerate() {
+            if room_labels[entry.room] == source {
+                starts[entry.cell] = starts[entry.cell].or(Some(idx));
+            } else {
+                goals[entry.cell] = goals[entry.cell].or(Some(idx));
+            }
+        }
+        (starts, goals)
+    }
+
+    /// The cheapest route from any entry of `source` to an entry of another component,
+    /// where each cell costs more the higher its noise. Returns the cells along with the
+    /// start and end entries.
+    fn cheapest_route(&self, grid: &Grid<T>, entries: &[Entry<T>], room_labels: &[usize], source: usize, noise: &[f64]) -> Option<(Vec<usize>, usize, usize)> {
+        let (starts, goals) = self.ends(grid, entries, room_labels, source);
+        let wander = self.corridor_options.wander as f64;
+        let mut costs = vec![f64::INFINITY; grid.free.len()];
+        let mut previous : Vec<Option<usize>> = vec![None; grid.free.len()];
+        let mut open = BinaryHeap::new();
+        for (cell, start) in starts.iter().enumerate() {
+            if start.is_some() {
+                costs[cell] = 0f64;
+                open.push(Candidate { cell, cost: 0f64 });
+            }
+        }
+        while let Some(Candidate { cell, cost }) = open.pop() {
+            if cost > costs[cell] {
+                continue;
+            }
+            if let Some(goal) = goals[cell] {
+                let mut cells = vec![cell];
+                while let Some(before) = previous[cells[cells.len() - 1]] {
+                    cells.push(before);
+                }
+                cells.reverse();
+                let start = starts[cells[0]].expect("route does not begin at an entry");
+                return Some((cells, start, goal));
+            }
+            for &step in &STEPS {
+                if let Some(next) = grid.neighbour(cell, step) {
+                    let next_cost = cost + 1f64 + wander * noise[next];
+                    if grid.free[next] && next_cost < costs[next] {
+                        costs[next] = next_cost;
+                        previous[next] = Some(cell);
+                        open.push(Candidate { cell: next, cost: next_cost });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// A walk from a random entry of `source` that steps closer to the nearest other
+    /// component, or in any free direction with a chance set by `wander`. Loops are cut out
+    /// as soon as the walk closes them. Gives up after a number of steps in proportion to
+    /// the grid size.
+    fn random_walk<R: Rng>(&self, grid: &Grid<T>, entries: &[Entry<T>], room_labels: &[usize], source: usize, rng: &mut R) -> Option<(Vec<usize>, usize, usize)> {
+        let (starts, goals) = self.ends(grid, entries, room_labels, source);
+        let mut distances : Vec<Option<usize>> = vec![None; grid.free.len()];
+        let mut queue = VecDeque::new();
+        for (cell, goal) in goals.iter().enumerate() {
+            if goal.is_some() {
+                distances[cell] = Some(0);
+                queue.push_back(cell);
+            }
+        }
+        while let Some(cell) = queue.pop_front() {
+            for &step in &STEPS {
+                if let Some(next) = grid.neighbour(cell, step) {
+                    if grid.free[next] && distances[next].is_none() {
+                        distances[next] = distances[cell].map(|distance| distance + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        let reachable : Vec<usize> = (0..grid.free.len()).filter(|&cell| starts[cell].is_some() && distances[cell].is_some()).collect();
+        if reachable.is_empty() {
+            return None;
+        }
+        let first = reachable[rng.gen_range(0, reachable.len())];
+        let stray = self.corridor_options.wander as f64 / (1f64 + self.corridor_options.wander as f64);
+        let mut cells = vec![first];
+        let mut position : Vec<Option<usize>> = vec![None; grid.free.len()];
+        position[first] = Some(0);
+        for _ in 0..grid.free.len() * 20 {
+            let cell = cells[cells.len() - 1];
+            if let Some(goal) = goals[cell] {
+                return Some((cells, starts[first].expect("walk does not begin at an entry"), goal));
+            }
+            let neighbours : Vec<usize> = STEPS.iter()
+                .filter_map(|&step| grid.neighbour(cell, step))
+                .filter(|&next| distances[next].is_some())
+                .collect();
+            let closer : Vec<usize> = neighbours.iter().cloned().filter(|&next| distances[next] < distances[cell]).collect();
+            let next = if rng.gen::<f64>() < stray || closer.is_empty() {
+                neighbours[rng.gen_range(0, neighbours.len())]
+            } else {
+                closer[rng.gen_range(0, closer.len())]
+            };
+            match position[next] {
+                Some(idx) => {
+                    for &erased in &cells[idx + 1..] {
+                        position[erased] = None;
+                    }
+                    cells.truncate(idx + 1);
+                },
+                None => {
+                    position[next] = Some(cells.len());
+                    cells.push(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// The door a corridor opens into the room of `entry`.
+    fn opening(&self, grid: &Grid<T>, entry: &Entry<T>) -> (Point<T>, Point<T>) {
+        let width = self.corridor_options.width;
+        let center = grid.center(entry.cell);
+        let band_low = |across: T| across - width / (T::ONE + T::ONE);
+        if STEPS[entry.step].0 != 0 {
+            (Point::new(entry.wall, band_low(center.y())), Point::new(entry.wall, band_low(center.y()) + width))
+        } else {
+            (Point::new(band_low(center.x()), entry.wall), Point::new(band_low(center.x()) + width, entry.wall))
+        }
+    }
+
+    /// Turns the cells of a route into a hallway running from wall to wall, returning it
+    /// together with the rectangles it covers.
+    fn corridor(&self, grid: &Grid<T>, cells: &[usize], start: &Entry<T>, end: &Entry<T>) -> (Hallway<T>, Vec<Rect<T>>) {
+        let width = self.corridor_options.width.to_f64();
+        // Integer corridors of odd width sit half a unit off the cell centers.
+        let shift = width / 2f64 - (self.corridor_options.width / (T::ONE + T::ONE)).to_f64();
+        let into = (-STEPS[start.step].0, -STEPS[start.step].1);
+        let out = STEPS[end.step];
+        let orthogonal = self.polyline(grid, cells, start, end, shift);
+        let rects = bands(&orthogonal, width / 2f64);
+        if self.corridor_options.diagonals {
+            let cut = cut_corners(grid, cells, into, out);
+            if cut.len() < cells.len() {
+                let outline = outline(&self.polyline(grid, &cut, start, end, shift), width / 2f64);
+                if segment::polygon_is_simple(&outline) {
+                    return (Hallway::with_rects(outline, rects.clone()), rects);
+                }
+            }
+        }
+        (Hallway::new(outline(&orthogonal, width / 2f64)), rects)
+    }
+
+    /// Wall to cell centers to wall, with straight runs joined into single segments.
+    fn polyline(&self, grid: &Grid<T>, cells: &[usize], start: &Entry<T>, end: &Entry<T>, shift: f64) -> Vec<Point<f64>> {
+        let center = |cell: usize| {
+            let center = grid.center(cell).cast::<f64>();
+            Point::new(center.x() + shift, center.y() + shift)
+        };
+        let wall_point = |entry: &Entry<T>| {
+            let center = center(entry.cell);
+            if STEPS[entry.step].0 != 0 { Point::new(entry.wall.to_f64(), center.y()) } else { Point::new(center.x(), entry.wall.to_f64()) }
+        };
+        let mut points = vec![wall_point(start)];
+        points.extend(cells.iter().map(|&cell| center(cell)));
+        points.push(wall_point(end));
+        let mut line : Vec<Point<f64>> = Vec::with_capacity(points.len());
+        for point in points {
+            if line.last().is_some_and(|last: &Point<f64>| (last.x() - point.x()).abs() < 1e-9 && (last.y() - point.y()).abs() < 1e-9) {
+                continue;
+            }
+            if line.len() >= 2 {
+                let (a, b) = (line[line.len() - 2], line[line.len() - 1]);
+                let turn = (b.x() - a.x()) * (point.y() - b.y()) - (b.y() - a.y()) * (point.x() - b.x());
+                let ahead = (b.x() - a.x()) * (point.x() - b.x()) + (b.y() - a.y()) * (point.y() - b.y());
+                if turn.abs() < 1e-9 && ahead > 0f64 {
+                    line.pop();
+                }
+            }
+            line.push(point);
+        }
+        line
+    }
+}
+
+/// Drops the corner cell of every staircase step and sharp bend where the cells on
+/// either side can be joined diagonally without turning back, and where the fourth cell
+/// of the square the diagonal crosses is free or part of the route.
+fn cut_corners<T: Scalar>(grid: &Grid<T>, cells: &[usize], into: (i64, i64), out: (i64, i64)) -> Vec<usize> {
+    let mut kept = vec![cells[0]];
+    let mut last_move = into;
+    for idx in 1..cells.len() {
+        let a = grid.coordinates(kept[kept.len() - 1]);
+        let b = grid.coordinates(cells[idx]);
+        let step_in = (b.0 - a.0, b.1 - a.1);
+        if idx + 1 < cells.len() && step_in.0.abs() + step_in.1.abs() == 1 {
+            let c = grid.coordinates(cells[idx + 1]);
+            let step_out = (c.0 - b.0, c.1 - b.1);
+            let diagonal = (c.0 - a.0, c.1 - a.1);
+            let after = if idx + 2 < cells.len() {
+                let d = grid.coordinates(cells[idx + 2]);
+                (d.0 - c.0, d.1 - c.1)
+            } else {
+                out
+            };
+            let dot = |a: (i64, i64), b: (i64, i64)| a.0 * b.0 + a.1 * b.1;
+            let fourth = grid.cell(a.0 + step_out.0, a.1 + step_out.1);
+            let clear = fourth.is_some_and(|fourth| grid.free[fourth] || cells.contains(&fourth));
+            if dot(step_in, step_out) == 0 && dot(diagonal, last_move) > 0 && dot(diagonal, after) > 0 && clear {
+                last_move = diagonal;
+                kept.push(cells[idx + 1]);
+                continue;
+            }
+        }
+        if kept[kept.len() - 1] != cells[idx] {
+            last_move = step_in;
+            kept.push(cells[idx]);
+        }
+    }
+    kept
+}
+
+/// The outline of a band `half` wide on each side of `line`, with mitred bends.
+fn outline<T: Scalar>(line: &[Point<f64>], half: f64) -> Vec<Point<T>> {
+    let normals : Vec<(f64, f64)> = line.windows(2).map(|pair| {
+        let (dx, dy) = (pair[1].x() - pair[0].x(), pair[1].y() - pair[0].y());
+        let length = (dx * dx + dy * dy).sqrt();
+        (-dy / length, dx / length)
+    }).collect();
+    let offset = |idx: usize, side: f64| {
+        let (nx, ny) = if idx == 0 {
+            normals[0]
+        } else if idx == normals.len() {
+            normals[idx - 1]
+        } else {
+            let (a, b) = (normals[idx - 1], normals[idx]);
+            let scale = 1f64 + a.0 * b.0 + a.1 * b.1;
+            ((a.0 + b.0) / scale, (a.1 + b.1) / scale)
+        };
+        Point::new(T::from_f64(line[idx].x() + nx * half * side), T::from_f64(line[idx].y() + ny * half * side))
+    };
+    let mut points : Vec<Point<T>> = (0..line.len()).map(|idx| offset(idx, 1f64)).collect();
+    points.extend((0..line.len()).rev().map(|idx| offset(idx, -1f64)));
+    points.dedup();
+    points
+}
+
+/// Non-overlapping rectangles covering a band `half` wide on each side of an axis aligned
+/// `line`. Each bend goes to the segment leading into it.
+fn bands<T: Scalar>(line: &[Point<f64>], half: f64) -> Vec<Rect<T>> {
+    let count = line.len() - 1;
+    (0..count).map(|idx| {
+        let (start, end) = (line[idx], line[idx + 1]);
+        let sign = |value: f64| if value > 0f64 { 1f64 } else if value < 0f64 { -1f64 } else { 0f64 };
+        let (dx, dy) = (sign(end.x() - start.x()), sign(end.y() - start.y()));
+        let grow_end = if idx + 1 < count { half } else { 0f64 };
+        let shrink_start = if idx > 0 { half } else { 0f64 };
+        let (start, end) = (
+            Point::new(start.x() + dx * shrink_start, start.y() + dy * shrink_start),
+            Point::new(end.x() + dx * grow_end, end.y() + dy * grow_end)
+        );
+        let (across_x, across_y) = (dy.abs() * half, dx.abs() * half);
+        Rect::new(
+            Point::new(T::from_f64(start.x() - across_x), T::from_f64(start.y() - across_y)),
+            Point::new(T::from_f64(end.x() + across_x), T::from_f64(end.y() + across_y))
+        )
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use room::Room;
+    use chunk::{Chunk, ChunkSplit};
+    use rand::{SeedableRng, Isaac64Rng};
+
+    fn room<T: Scalar>(lower_left: (T, T), upper_right: (T, T)) -> Room<T> {
+        Room::new(Chunk::new(Point::new(lower_left.0, lower_left.1), Point::new(upper_right.0, upper_right.1), ChunkSplit::Vertical))
+    }
+
+    fn apart() -> Dungeon {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(room((0f32, 0f32), (20f32, 20f32)));
+        dungeon.add_room(room((80f32, 40f32), (100f32, 60f32)));
+        dungeon.add_room(room((0f32, 80f32), (30f32, 100f32)));
+        dungeon
+    }
+
+    fn check<T: Scalar>(dungeon: &Dungeon<T>, keep_out: &[Rect<T>]) {
+        assert!(dungeon.is_connected());
+        for hallway in dungeon.hallways() {
+            assert!(segment::polygon_is_simple(hallway.points()));
+            for rect in hallway.rects() {
+                assert!(!dungeon.rooms().iter().any(|room| room.rect().intersects(&rect)));
+                assert!(!keep_out.iter().any(|zone| zone.intersects(&rect)));
+            }
+        }
+        let corridor_doors = dungeon.doors().iter().filter(|door| door.kind() == DoorKind::Corridor).count();
+        assert_eq!(2 * dungeon.hallways().len(), corridor_doors);
+    }
+
+    #[test]
+    fn test_noise_corridors() {
+        let mut dungeon = apart();
+        let corridor_options = CorridorOptions::new(3f32, CorridorStyle::Noise);
+        let added = CorridorBuilder::new(&corridor_options).connect(&mut dungeon, &[], &mut Isaac64Rng::from_seed(&[1]));
+        assert_eq!(2, added);
+        check(&dungeon, &[]);
+    }
+
+    #[test]
+    fn test_random_walk_corridors() {
+        for seed in 0..10 {
+            let mut dungeon = apart();
+            let mut corridor_options = CorridorOptions::new(2f32, CorridorStyle::RandomWalk);
+            corridor_options.wander = 2f32;
+            corridor_options.diagonals = true;
+            CorridorBuilder::new(&corridor_options).connect(&mut dungeon, &[], &mut Isaac64Rng::from_seed(&[seed]));
+            check(&dungeon, &[]);
+        }
+    }
+
+    #[test]
+    fn test_diagonal_corridors() {
+        let mut diagonal = 0;
+        for seed in 0..10 {
+            let mut dungeon = apart();
+            let mut corridor_options = CorridorOptions::new(2f32, CorridorStyle::Noise);
+            corridor_options.diagonals = true;
+            CorridorBuilder::new(&corridor_options).connect(&mut dungeon, &[], &mut Isaac64Rng::from_seed(&[seed]));
+            check(&dungeon, &[]);
+            diagonal += dungeon.hallways().iter()
+                .filter(|hallway| hallway.edges().iter().any(|&(start, end)| start.x() != end.x() && start.y() != end.y()))
+                .count();
+        }
+        assert!(diagonal > 0);
+    }
+
+    #[test]
+    fn test_corridor_around_keep_out() {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(room((0i32, 0i32), (20i32, 20i32)));
+        dungeon.add_room(room((60i32, 0i32), (80i32, 20i32)));
+        let keep_out = [Rect::new(Point::new(25i32, -20i32), Point::new(55i32, 25i32))];
+        let corridor_options = CorridorOptions::new(3i32, CorridorStyle::Noise);
+        assert_eq!(1, CorridorBuilder::new(&corridor_options).connect(&mut dungeon, &keep_out, &mut Isaac64Rng::from_seed(&[4])));
+        check(&dungeon, &keep_out);
+        let door = &dungeon.doors()[0];
+        assert_eq!(3, segment::length(&(*door.start(), *door.end())));
+    }
+
+    #[test]
+    fn test_no_route() {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(room((0f32, 0f32), (20f32, 20f32)));
+        dungeon.add_room(room((60f32, 0f32), (80f32, 20f32)));
+        let keep_out = [Rect::new(Point::new(30f32, -100f32), Point::new(50f32, 100f32))];
+        let corridor_options = CorridorOptions::new(2f32, CorridorStyle::RandomWalk);
+        assert_eq!(0, CorridorBuilder::new(&corridor_options).connect(&mut dungeon, &keep_out, &mut Isaac64Rng::from_seed(&[1])));
+        assert!(!dungeon.is_connected());
+    }
+}
@@ -0,0 +1,38 @@
+use scalar::Scalar;
+
+/// How a corridor finds its way through free space.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CorridorStyle {
+    /// The cheapest route over ground whose cost varies at random, which bends the
+    /// corridor around the expensive patches.
+    Noise,
+    /// A walk that heads for the other room and now and then strays, with any loops it
+    /// makes cut out again.
+    RandomWalk,
+}
+
+/// Winding corridors through the space rooms and hallways leave empty, joining parts of
+/// the dungeon that share no wall, like separate areas or rooms on either side of a
+/// keep-out zone.
+pub struct CorridorOptions<T = f32> {
+    pub width: T,
+    pub style: CorridorStyle,
+    /// How far corridors stray from the shortest route, from 0 upwards.
+    pub wander: f32,
+    /// Lets corridors cut the corners of their bends at 45 degrees.
+    pub diagonals: bool,
+    /// How far outside the rooms and hallways corridors may run.
+    pub margin: T,
+}
+
+impl<T: Scalar> CorridorOptions<T> {
+    pub fn new(width: T, style: CorridorStyle) -> CorridorOptions<T> {
+        CorridorOptions {
+            width,
+            style,
+            wander: 1f32,
+            diagonals: false,
+            margin: width + width + width + width
+        }
+    }
+}
@@ -1,16 +1,24 @@
-pub struct DimensionOptions {
-    pub min_width: f32,
-    pub min_height: f32,
-    pub min_area: f32,
-    pub max_area: Option<f32>
+use scalar::Scalar;
+use sizedistribution::SizeDistribution;
+
+pub struct DimensionOptions<T = f32> {
+    pub min_width: T,
+    pub min_height: T,
+    pub min_area: T,
+    pub max_area: Option<T>,
+    /// Chance that a chunk below `max_area` stops splitting early.
+    pub stop_chance: f32,
+    /// Largest ratio of a room's long side to its short side the splitter aims for.
+    pub max_aspect: Option<f32>,
+    pub size_distribution: Option<SizeDistribution<T>>
 }
 
-impl DimensionOptions {
-    pub fn new(min_width: f32, min_height: f32, min_area: f32) -> DimensionOptions {
-        DimensionOptions { min_width: min_width, min_height: min_height, min_area: min_area, max_area: None }
+impl<T: Scalar> DimensionOptions<T> {
+    pub fn new(min_width: T, min_height: T, min_area: T) -> DimensionOptions<T> {
+        DimensionOptions { min_width, min_height, min_area, max_area: None, stop_chance: 0.25f32, max_aspect: None, size_distribution: None }
     }
 
-    pub fn new_with_max(min_width: f32, min_height: f32, min_area: f32, max_area: f32) -> DimensionOptions {
-        DimensionOptions { min_width: min_width, min_height: min_height, min_area: min_area, max_area: Some(max_area) }
+    pub fn new_with_max(min_width: T, min_height: T, min_area: T, max_area: T) -> DimensionOptions<T> {
+        DimensionOptions { min_width, min_height, min_area, max_area: Some(max_area), stop_chance: 0.25f32, max_aspect: None, size_distribution: None }
     }
 }
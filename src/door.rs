@@ -0,0 +1,99 @@
+use point::Point;
+use scalar::Scalar;
+use room::RoomId;
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Region {
+    Room(RoomId),
+    Hallway(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DoorKind {
+    Hallway,
+    Loop,
+    Connectivity,
+    Corridor,
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Region::Room(id) => write!(f, "{}", id),
+            Region::Hallway(idx) => write!(f, "hallway {}", idx)
+        }
+    }
+}
+
+impl DoorKind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            DoorKind::Hallway => "hallway",
+            DoorKind::Loop => "loop",
+            DoorKind::Connectivity => "connectivity",
+            DoorKind::Corridor => "corridor"
+        }
+    }
+}
+
+pub struct Door<T = f32> {
+    from: Region,
+    to: Region,
+    start: Point<T>,
+    end: Point<T>,
+    kind: DoorKind
+}
+
+impl<T: Scalar> Door<T> {
+    pub fn new(from: Region, to: Region, start: Point<T>, end: Point<T>, kind: DoorKind) -> Door<T> {
+        Door { from, to, start, end, kind }
+    }
+
+    pub fn from(&self) -> Region {
+        self.from
+    }
+
+    pub fn to(&self) -> Region {
+        self.to
+    }
+
+    pub fn start(&self) -> &Point<T> {
+        &self.start
+    }
+
+    pub fn end(&self) -> &Point<T> {
+        &self.end
+    }
+
+    pub fn kind(&self) -> DoorKind {
+        self.kind
+    }
+
+    pub fn width(&self) -> T {
+        (self.end.x() - self.start.x()).abs() + (self.end.y() - self.start.y()).abs()
+    }
+
+    pub fn connects(&self, a: Region, b: Region) -> bool {
+        (self.from == a && self.to == b) || (self.from == b && self.to == a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+
+    #[test]
+    fn test_width() {
+        let door = Door::new(Region::Room(RoomId::new(0)), Region::Hallway(0), Point::new(2f32, 5f32), Point::new(2f32, 8f32), DoorKind::Hallway);
+        assert_eq!(3f32, door.width());
+    }
+
+    #[test]
+    fn test_connects() {
+        let door = Door::new(Region::Room(RoomId::new(0)), Region::Room(RoomId::new(1)), Point::new(2f32, 5f32), Point::new(2f32, 8f32), DoorKind::Loop);
+        assert!(door.connects(Region::Room(RoomId::new(1)), Region::Room(RoomId::new(0))));
+        assert!(!door.connects(Region::Room(RoomId::new(1)), Region::Hallway(0)));
+    }
+}
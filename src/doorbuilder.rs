@@ -0,0 +1,56 @@
+use door::{Door, DoorKind, Region};
+use room::{Room, RoomId};
+use hallway::Hallway;
+use scalar::Scalar;
+use segment;
+
+pub struct DoorBuilder<T = f32> {
+    door_width: T
+}
+
+impl<T: Scalar> DoorBuilder<T> {
+    pub fn new(door_width: T) -> DoorBuilder<T> {
+        DoorBuilder { door_width }
+    }
+
+    /// Places one door on the longest wall each room shares with each hallway.
+    pub fn hallway_doors(&self, rooms: &[Room<T>], hallways: &[Hallway<T>]) -> Vec<Door<T>> {
+        let mut doors = Vec::new();
+        let hallway_edges : Vec<Vec<segment::Segment<T>>> = hallways.iter().map(|hallway| hallway.edges()).collect();
+        for (room_idx, room) in rooms.iter().enumerate() {
+            let room_edges = room.rect().edges();
+            for (hallway_idx, edges) in hallway_edges.iter().enumerate() {
+                if let Some(shared) = segment::longest_shared_segment(&room_edges, edges) {
+                    let opening = segment::centered(&shared, self.door_width);
+                    doors.push(Door::new(Region::Room(RoomId::new(room_idx)), Region::Hallway(hallway_idx), opening.0, opening.1, DoorKind::Hallway));
+                }
+            }
+        }
+        doors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use room::{Room, RoomId};
+    use hallway::Hallway;
+    use chunk::{Chunk, ChunkSplit};
+
+    #[test]
+    fn test_hallway_doors() {
+        let rooms = vec![
+            Room::new(Chunk::new(Point::new(0f32, 2f32), Point::new(10f32, 12f32), ChunkSplit::Vertical)),
+            Room::new(Chunk::new(Point::new(0f32, 20f32), Point::new(10f32, 30f32), ChunkSplit::Vertical)),
+        ];
+        let hallways = vec![Hallway::new(vec![
+            Point::new(0f32, 0f32), Point::new(0f32, 2f32), Point::new(20f32, 2f32), Point::new(20f32, 0f32)
+        ])];
+        let doors = DoorBuilder::new(2f32).hallway_doors(&rooms, &hallways);
+        assert_eq!(1, doors.len());
+        assert!(doors[0].connects(Region::Room(RoomId::new(0)), Region::Hallway(0)));
+        assert!(*doors[0].start() == Point::new(4f32, 2f32));
+        assert!(*doors[0].end() == Point::new(6f32, 2f32));
+    }
+}
@@ -1,29 +1,277 @@
-use room::Room;
+use room::{Room, RoomId};
 use hallway::Hallway;
+use door::{Door, DoorKind, Region};
+use roomgraph::RoomGraph;
+use roomrole::RoomRole;
+use roleoptions::RoleOptions;
+use roleassigner::RoleAssigner;
+use spawnpoint::SpawnPoint;
+use spawnoptions::SpawnOptions;
+use spawnbuilder::SpawnBuilder;
+use hallwaybuilder::HallwayBuilder;
+use doorbuilder::DoorBuilder;
+use connectivityoptions::ConnectivityOptions;
+use connectivitybuilder::ConnectivityBuilder;
+use loopoptions::LoopOptions;
+use loopbuilder::LoopBuilder;
+use dungeonjson::{self, ImportError};
+use wall::Wall;
+use walloptions::WallOptions;
+use wallbuilder::WallBuilder;
+use mesh::Mesh;
+use meshoptions::MeshOptions;
+use meshbuilder::MeshBuilder;
+use tilemap::TileMap;
+use tiledoptions::TiledOptions;
+use tilemapbuilder::TileMapBuilder;
+use navmesh::{NavMesh, PathError};
+use spatialindex::SpatialIndex;
+use prefab::PrefabPlacement;
+use sizedistribution::SizeReport;
+use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+use rand::Rng;
+use std::sync::OnceLock;
 
-pub struct Dungeon {
-    rooms: Vec<Room>,
-    hallways: Vec<Hallway>
+pub struct Dungeon<T = f32> {
+    rooms: Vec<Room<T>>,
+    hallways: Vec<Hallway<T>>,
+    doors: Vec<Door<T>>,
+    spawn_points: Vec<SpawnPoint<T>>,
+    walls: Vec<Wall<T>>,
+    prefabs: Vec<PrefabPlacement<T>>,
+    fixed_rooms: Vec<RoomId>,
+    size_report: Option<SizeReport<T>>,
+    index: OnceLock<SpatialIndex<T>>
 }
 
-impl Dungeon {
-    pub fn new() -> Dungeon {
-        Dungeon { rooms: Vec::new(), hallways: Vec::new() }
+impl<T: Scalar> Dungeon<T> {
+    pub fn new() -> Dungeon<T> {
+        Dungeon { rooms: Vec::new(), hallways: Vec::new(), doors: Vec::new(), spawn_points: Vec::new(), walls: Vec::new(), prefabs: Vec::new(), fixed_rooms: Vec::new(), size_report: None, index: OnceLock::new() }
     }
 
-    pub fn add_room(&mut self, room: Room) {
-        self.rooms.push(room)
+    /// Adds one more room and returns its id.
+    pub fn add_room(&mut self, room: Room<T>) -> RoomId {
+        self.rooms.push(room);
+        self.index = OnceLock::new();
+        RoomId::new(self.rooms.len() - 1)
     }
 
-    pub fn add_hallways(&mut self, hallways: Vec<Hallway>) {
-        self.hallways = hallways
+    /// Replaces the hallways. Use `add_hallway` to add one more.
+    pub fn add_hallways(&mut self, hallways: Vec<Hallway<T>>) {
+        self.hallways = hallways;
+        self.index = OnceLock::new();
     }
 
-    pub fn rooms(&self) -> &[Room] {
+    /// Adds one more hallway and returns its index.
+    pub fn add_hallway(&mut self, hallway: Hallway<T>) -> usize {
+        self.hallways.push(hallway);
+        self.index = OnceLock::new();
+        self.hallways.len() - 1
+    }
+
+    pub fn add_door(&mut self, door: Door<T>) {
+        self.doors.push(door)
+    }
+
+    pub fn add_spawn_point(&mut self, spawn_point: SpawnPoint<T>) {
+        self.spawn_points.push(spawn_point);
+    }
+
+    pub fn rooms(&self) -> &[Room<T>] {
         &self.rooms[..]
     }
 
-    pub fn hallways(&self) -> &[Hallway] {
+    /// The ids of `rooms`, in the same order.
+    pub fn room_ids(&self) -> impl Iterator<Item = RoomId> {
+        (0..self.rooms.len()).map(RoomId::new)
+    }
+
+    pub fn room(&self, id: RoomId) -> &Room<T> {
+        &self.rooms[id.index()]
+    }
+
+    /// Hands out a room for changes, dropping the cached spatial index like `add_room`
+    /// does.
+    pub fn room_mut(&mut self, id: RoomId) -> &mut Room<T> {
+        self.index = OnceLock::new();
+        &mut self.rooms[id.index()]
+    }
+
+    pub fn hallways(&self) -> &[Hallway<T>] {
         &self.hallways[..]
     }
+
+    pub fn doors(&self) -> &[Door<T>] {
+        &self.doors[..]
+    }
+
+    pub fn add_prefab(&mut self, placement: PrefabPlacement<T>) {
+        self.prefabs.push(placement)
+    }
+
+    pub fn prefabs(&self) -> &[PrefabPlacement<T>] {
+        &self.prefabs[..]
+    }
+
+    pub fn prefab_in(&self, room: RoomId) -> Option<&PrefabPlacement<T>> {
+        self.prefabs.iter().find(|placement| placement.room() == room)
+    }
+
+    /// Records that a room was placed by hand rather than generated.
+    pub fn add_fixed_room(&mut self, room: RoomId) {
+        self.fixed_rooms.push(room)
+    }
+
+    /// The hand placed rooms, in the order they were given to the builder.
+    pub fn fixed_rooms(&self) -> &[RoomId] {
+        &self.fixed_rooms[..]
+    }
+
+    pub fn set_size_report(&mut self, size_report: SizeReport<T>) {
+        self.size_report = Some(size_report)
+    }
+
+    /// How closely the rooms match the size distribution and aspect limit they were
+    /// generated with, when either was set.
+    pub fn size_report(&self) -> Option<&SizeReport<T>> {
+        self.size_report.as_ref()
+    }
+
+    pub fn loops(&self) -> Vec<&Door<T>> {
+        self.doors.iter().filter(|door| door.kind() == DoorKind::Loop).collect()
+    }
+
+    /// Marks the entrance, the room farthest from it and every room matching one of the
+    /// configured rules.
+    pub fn assign_roles<R: Rng>(&mut self, role_options: &RoleOptions<T>, rng: &mut R) {
+        let roles = RoleAssigner::new(role_options).assign(self, rng);
+        for (id, role) in roles {
+            self.room_mut(id).add_role(role);
+        }
+    }
+
+    /// Joins hallways that touch or overlap into single outlines, the way the builder joins
+    /// the hallways it strips. Hallway indices change, so doors and spawn points in
+    /// hallways are dropped, and corridors with diagonal walls become their rectangles.
+    pub fn merge_hallways(&mut self) {
+        let mut hallway_builder = HallwayBuilder::new();
+        for rect in self.hallways.iter().flat_map(|hallway| hallway.rects()) {
+            hallway_builder.add_rect(rect);
+        }
+        self.hallways = hallway_builder.merge_hallways();
+        let in_hallway = |region: Region| matches!(region, Region::Hallway(_));
+        self.doors.retain(|door| !in_hallway(door.from()) && !in_hallway(door.to()));
+        self.spawn_points.retain(|spawn_point| !in_hallway(spawn_point.region()));
+        self.index = OnceLock::new();
+    }
+
+    /// Replaces the doors between rooms and hallways with one on the longest wall each
+    /// room shares with each hallway.
+    pub fn place_hallway_doors(&mut self, door_width: T) {
+        self.doors.retain(|door| door.kind() != DoorKind::Hallway);
+        let doors = DoorBuilder::new(door_width).hallway_doors(&self.rooms, &self.hallways);
+        self.doors.extend(doors);
+    }
+
+    /// Adds doors between neighbouring rooms until every room that shares a wall with
+    /// another is reachable. Returns the number of doors added.
+    pub fn connect_rooms<R: Rng>(&mut self, connectivity_options: &ConnectivityOptions<T>, rng: &mut R) -> usize {
+        ConnectivityBuilder::new(connectivity_options).connect(self, rng)
+    }
+
+    /// Adds doors between neighbouring rooms that are far apart through the doors there
+    /// are. Returns the number of doors added.
+    pub fn add_loops<R: Rng>(&mut self, loop_options: &LoopOptions<T>, rng: &mut R) -> usize {
+        LoopBuilder::new(loop_options).add_loops(self, rng)
+    }
+
+    pub fn rooms_with_role(&self, role: &RoomRole) -> Vec<RoomId> {
+        self.room_ids().filter(|&id| self.room(id).has_role(role)).collect()
+    }
+
+    /// Scatters spawn points through rooms and hallways, replacing any placed before.
+    pub fn place_spawns<R: Rng>(&mut self, spawn_options: &SpawnOptions<T>, rng: &mut R) {
+        self.spawn_points = SpawnBuilder::new(spawn_options).place(self, rng);
+    }
+
+    pub fn spawn_points(&self) -> &[SpawnPoint<T>] {
+        &self.spawn_points[..]
+    }
+
+    /// Stands walls on the boundaries of the rooms and hallways with the doors left open,
+    /// replacing any built before.
+    pub fn build_walls(&mut self, wall_options: &WallOptions<T>) {
+        self.walls = WallBuilder::new(wall_options).walls(self);
+    }
+
+    pub fn walls(&self) -> &[Wall<T>] {
+        &self.walls[..]
+    }
+
+    /// Extrudes the floors, and any walls from `build_walls`, into a triangle mesh ready
+    /// for `Mesh::to_obj` or `Mesh::to_gltf`.
+    pub fn to_mesh(&self, mesh_options: &MeshOptions<T>) -> Mesh {
+        MeshBuilder::new(mesh_options).mesh(self)
+    }
+
+    /// Lays the dungeon out on a tile grid for the Tiled editor, with the rooms, doors and
+    /// spawn points as objects, ready for `TileMap::to_json` or `TileMap::to_tmx`.
+    pub fn to_tile_map(&self, tiled_options: &TiledOptions<T>) -> TileMap {
+        TileMapBuilder::new(tiled_options).tile_map(self)
+    }
+
+    /// Writes the rooms, hallways, doors and spawn points as JSON that `from_json` reads.
+    pub fn to_json(&self) -> String {
+        dungeonjson::write(self)
+    }
+
+    /// Reads a layout written by `to_json`, or edited by hand, so the passes above can run
+    /// on it again. Rooms come back at depth zero of the split tree.
+    pub fn from_json(json: &str) -> Result<Dungeon<T>, ImportError> {
+        dungeonjson::read(json)
+    }
+
+    /// Finds the shortest walkable polyline between two points through rooms, doors and
+    /// hallways. This builds a `NavMesh` on every call, so build one directly when
+    /// running many queries.
+    pub fn find_path(&self, from: &Point<T>, to: &Point<T>) -> Result<Vec<Point<T>>, PathError> {
+        let mesh = NavMesh::new(self);
+        let path = mesh.find_path(from, to)?;
+        Ok(mesh.straighten(&path))
+    }
+
+    /// Grid over the rooms and hallways, built on first use and rebuilt after either
+    /// changes.
+    pub fn spatial_index(&self) -> &SpatialIndex<T> {
+        self.index.get_or_init(|| SpatialIndex::new(self))
+    }
+
+    pub fn region_at(&self, point: &Point<T>) -> Option<Region> {
+        self.spatial_index().region_at(point)
+    }
+
+    pub fn rooms_in(&self, rect: &Rect<T>) -> Vec<RoomId> {
+        self.spatial_index().rooms_in(rect)
+    }
+
+    pub fn nearest_room(&self, point: &Point<T>) -> Option<RoomId> {
+        self.spatial_index().nearest_room(point)
+    }
+
+    /// Groups rooms and hallways that can reach each other through doors.
+    pub fn components(&self) -> Vec<Vec<Region>> {
+        RoomGraph::new(self.rooms.len(), self.hallways.len(), &self.doors).components()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.components().len() <= 1
+    }
+}
+
+impl<T: Scalar> Default for Dungeon<T> {
+    fn default() -> Dungeon<T> {
+        Dungeon::new()
+    }
 }
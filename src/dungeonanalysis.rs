@@ -0,0 +1,148 @@
+use dungeon::Dungeon;
+use door::Region;
+use room::RoomId;
+use roomgraph::RoomGraph;
+use scalar::Scalar;
+
+pub struct RoomMetrics {
+    distance: Option<usize>,
+    degree: usize,
+    bsp_depth: usize
+}
+
+impl RoomMetrics {
+    /// Number of doors crossed to reach the room from the start room, `None` when unreachable.
+    pub fn distance(&self) -> Option<usize> {
+        self.distance
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn is_dead_end(&self) -> bool {
+        self.degree == 1
+    }
+
+    pub fn bsp_depth(&self) -> usize {
+        self.bsp_depth
+    }
+}
+
+pub struct DungeonAnalysis {
+    start: RoomId,
+    rooms: Vec<RoomMetrics>
+}
+
+impl DungeonAnalysis {
+    /// Measures every room from `start`, or returns `None` when `start` is not one of
+    /// the dungeon's rooms, which is always the case in a dungeon without rooms.
+    pub fn new<T: Scalar>(dungeon: &Dungeon<T>, start: RoomId) -> Option<DungeonAnalysis> {
+        if start.index() >= dungeon.rooms().len() {
+            return None;
+        }
+        let graph = RoomGraph::new(dungeon.rooms().len(), dungeon.hallways().len(), dungeon.doors());
+        let distances = graph.distances(Region::Room(start));
+        let rooms = dungeon.room_ids().map(|id| {
+            RoomMetrics { 
+                distance: distances[graph.node(Region::Room(id))], 
+                degree: graph.degree(Region::Room(id)), 
+                bsp_depth: dungeon.room(id).bsp_depth() 
+            }
+        }).collect();
+        Some(DungeonAnalysis { start, rooms })
+    }
+
+    pub fn start(&self) -> RoomId {
+        self.start
+    }
+
+    pub fn room(&self, id: RoomId) -> &RoomMetrics {
+        &self.rooms[id.index()]
+    }
+
+    pub fn rooms(&self) -> &[RoomMetrics] {
+        &self.rooms[..]
+    }
+
+    pub fn max_distance(&self) -> Option<usize> {
+        self.rooms.iter().filter_map(|metrics| metrics.distance).max()
+    }
+
+    pub fn farthest_rooms(&self) -> Vec<RoomId> {
+        let max_distance = self.max_distance();
+        self.room_ids(|metrics| metrics.distance == max_distance)
+    }
+
+    pub fn dead_ends(&self) -> Vec<RoomId> {
+        self.room_ids(|metrics| metrics.is_dead_end())
+    }
+
+    pub fn unreachable(&self) -> Vec<RoomId> {
+        self.room_ids(|metrics| metrics.distance.is_none())
+    }
+
+    fn room_ids<F: Fn(&RoomMetrics) -> bool>(&self, keep: F) -> Vec<RoomId> {
+        (0..self.rooms.len()).filter(|&idx| keep(&self.rooms[idx])).map(RoomId::new).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use room::Room;
+    use hallway::Hallway;
+    use dungeon::Dungeon;
+    use door::{Door, DoorKind, Region};
+    use chunk::{Chunk, ChunkSplit};
+    use dimensionoptions::DimensionOptions;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_analysis() {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0f32, 0f32), Point::new(10f32, 10f32), ChunkSplit::Horizontal)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(10f32, 0f32), Point::new(20f32, 10f32), ChunkSplit::Horizontal)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(20f32, 0f32), Point::new(30f32, 10f32), ChunkSplit::Horizontal)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(40f32, 0f32), Point::new(50f32, 10f32), ChunkSplit::Horizontal)));
+        dungeon.add_hallways(vec![Hallway::new(vec![
+            Point::new(0f32, 10f32), Point::new(0f32, 12f32), Point::new(30f32, 12f32), Point::new(30f32, 10f32)
+        ])]);
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(0)), Region::Hallway(0), Point::new(4f32, 10f32), Point::new(6f32, 10f32), DoorKind::Hallway));
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(2)), Region::Hallway(0), Point::new(24f32, 10f32), Point::new(26f32, 10f32), DoorKind::Hallway));
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(2)), Region::Room(RoomId::new(1)), Point::new(20f32, 4f32), Point::new(20f32, 6f32), DoorKind::Loop));
+
+        let analysis = DungeonAnalysis::new(&dungeon, RoomId::new(0)).unwrap();
+        assert_eq!(RoomId::new(0), analysis.start());
+        assert_eq!(Some(0), analysis.room(RoomId::new(0)).distance());
+        assert_eq!(Some(3), analysis.room(RoomId::new(1)).distance());
+        assert_eq!(Some(2), analysis.room(RoomId::new(2)).distance());
+        assert_eq!(None, analysis.room(RoomId::new(3)).distance());
+        assert_eq!(2, analysis.room(RoomId::new(2)).degree());
+        assert_eq!(vec![RoomId::new(0), RoomId::new(1)], analysis.dead_ends());
+        assert_eq!(vec![RoomId::new(1)], analysis.farthest_rooms());
+        assert_eq!(vec![RoomId::new(3)], analysis.unreachable());
+    }
+
+    #[test]
+    fn test_bsp_depth() {
+        let mut dungeon = Dungeon::new();
+        let mut chunk = Chunk::new(Point::new(0f32, 0f32), Point::new(100f32, 100f32), ChunkSplit::Horizontal);
+        let new_chunk = chunk.split(&DimensionOptions::new(10f32, 10f32, 100f32), &mut thread_rng()).unwrap();
+        dungeon.add_room(Room::new(chunk));
+        dungeon.add_room(Room::new(new_chunk));
+        let analysis = DungeonAnalysis::new(&dungeon, RoomId::new(0)).unwrap();
+        assert_eq!(1, analysis.room(RoomId::new(0)).bsp_depth());
+        assert_eq!(1, analysis.room(RoomId::new(1)).bsp_depth());
+    }
+
+    #[test]
+    fn test_missing_start() {
+        let mut dungeon : Dungeon = Dungeon::new();
+        assert!(DungeonAnalysis::new(&dungeon, RoomId::new(0)).is_none());
+        dungeon.add_room(Room::from_points(Point::new(0f32, 0f32), Point::new(10f32, 10f32)));
+        assert!(DungeonAnalysis::new(&dungeon, RoomId::new(1)).is_none());
+        assert_eq!(Some(0), DungeonAnalysis::new(&dungeon, RoomId::new(0)).unwrap().room(RoomId::new(0)).distance());
+    }
+}
@@ -1,107 +1,439 @@
-use room::Room;
-use chunk::{Chunk, ChunkSplit};
+use room::{Room, RoomId};
+use chunk::{Chunk, ChunkSplit, share_hallway_budget};
 use point::Point;
+use rect::Rect;
 use dungeon::Dungeon;
-use rand::{Rng, ThreadRng, thread_rng};
+use rand::{Rng, SeedableRng, Isaac64Rng, thread_rng};
 use dimensionoptions::DimensionOptions;
-use hallwayoptions::HallwayOptions;
+use hallwayoptions::{HallwayOptions, HallwayBudget, HallwayPlacement};
 use hallwaybuilder::HallwayBuilder;
-use std::collections::BinaryHeap;
-
-pub struct DungeonBuilder {
-    chunks: BinaryHeap<Chunk>,
-    rng: ThreadRng,
-    dimension_options: Option<DimensionOptions>,
-    hallway_options: Option<HallwayOptions>,
-    total_area: f32,
+use loopoptions::LoopOptions;
+use connectivityoptions::ConnectivityOptions;
+use corridoroptions::CorridorOptions;
+use corridorbuilder::CorridorBuilder;
+use roleoptions::RoleOptions;
+use spawnoptions::SpawnOptions;
+use walloptions::WallOptions;
+use prefab::{Prefab, PrefabPlacement};
+use sizedistribution::SizeReport;
+use splitstrategy::{SplitStrategy, AlternatingSplit};
+use splitorder::{SplitOrder, ChunkQueue};
+use scalar::Scalar;
+
+/// Options `try_build` cannot lay out. Fixed rooms are counted in the order they were added.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BuildError {
+    MissingDimensionOptions,
+    /// The fixed room does not lie wholly inside one of the areas.
+    FixedRoomOutsideArea(usize),
+    /// The fixed room overlaps a keep out zone or an earlier fixed room.
+    FixedRoomOverlaps(usize),
+}
+
+pub struct DungeonBuilder<T = f32> {
+    areas: Vec<Rect<T>>,
+    hallway_areas: Vec<Rect<T>>,
+    rng: Isaac64Rng,
+    seed: Option<u64>,
+    dimension_options: Option<DimensionOptions<T>>,
+    hallway_options: Option<HallwayOptions<T>>,
+    loop_options: Option<LoopOptions<T>>,
+    connectivity_options: Option<ConnectivityOptions<T>>,
+    corridor_options: Option<CorridorOptions<T>>,
+    role_options: Option<RoleOptions<T>>,
+    spawn_options: Option<SpawnOptions<T>>,
+    wall_options: Option<WallOptions<T>>,
+    prefabs: Vec<Prefab<T>>,
+    fixed_rooms: Vec<Rect<T>>,
+    keep_out: Vec<Rect<T>>,
+    split_strategy: Box<dyn SplitStrategy<T>>,
+    split_order: SplitOrder,
 }
 
-impl DungeonBuilder  {
-    pub fn new() -> DungeonBuilder  {
+impl<T: Scalar> DungeonBuilder<T>  {
+    pub fn new() -> DungeonBuilder<T>  {
         DungeonBuilder { 
-            chunks: BinaryHeap::new(), 
-            rng: thread_rng(), 
+            areas: Vec::new(),
+            hallway_areas: Vec::new(),
+            rng: thread_rng().gen(), 
+            seed: None,
             dimension_options: None, 
             hallway_options: None,
-            total_area: 0f32,
+            loop_options: None,
+            connectivity_options: None,
+            corridor_options: None,
+            role_options: None,
+            spawn_options: None,
+            wall_options: None,
+            prefabs: Vec::new(),
+            fixed_rooms: Vec::new(),
+            keep_out: Vec::new(),
+            split_strategy: Box::new(AlternatingSplit),
+            split_order: SplitOrder::LargestFirst,
         }
     }
 
-    pub fn in_area(&mut self, lower_left: Point, upper_right: Point) -> &mut DungeonBuilder {
-        let mut chunk_split = ChunkSplit::Horizontal;
-        if self.rng.gen_weighted_bool(2) {
-            chunk_split = ChunkSplit::Vertical;
-        }
-        let chunk = Chunk::new(lower_left, upper_right, chunk_split);
-        self.total_area = chunk.area();
-        self.chunks.push(chunk);
-        self        
+    /// Makes every build repeat the same layout for the same options. The generator is
+    /// reseeded at the start of each build.
+    pub fn with_seed(&mut self, seed: u64) -> &mut DungeonBuilder<T> {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn in_area(&mut self, lower_left: Point<T>, upper_right: Point<T>) -> &mut DungeonBuilder<T> {
+        self.areas.push(Rect::new(lower_left, upper_right));
+        self
+    }
+
+    /// Reserves a rectangle next to the areas as hallway before any room is split off.
+    /// Reserved rectangles count towards the hallway percentage.
+    pub fn with_hallway_area(&mut self, lower_left: Point<T>, upper_right: Point<T>) -> &mut DungeonBuilder<T> {
+        self.hallway_areas.push(Rect::new(lower_left, upper_right));
+        self
+    }
+
+    /// Places a room at an exact spot inside one of the areas. Chunks are cut along its
+    /// walls before any random split, and the rest is generated around it.
+    pub fn with_fixed_room(&mut self, lower_left: Point<T>, upper_right: Point<T>) -> &mut DungeonBuilder<T> {
+        self.fixed_rooms.push(Rect::new(lower_left, upper_right));
+        self
+    }
+
+    /// Keeps a part of the areas free of rooms and hallways.
+    pub fn with_keep_out(&mut self, lower_left: Point<T>, upper_right: Point<T>) -> &mut DungeonBuilder<T> {
+        self.keep_out.push(Rect::new(lower_left, upper_right));
+        self
     }
 
-    pub fn with_dimension_options(&mut self, dimension_options: DimensionOptions) -> &mut DungeonBuilder {
+    pub fn with_dimension_options(&mut self, dimension_options: DimensionOptions<T>) -> &mut DungeonBuilder<T> {
         self.dimension_options = Some(dimension_options);
         self
     }
 
-    pub fn with_hallway_options(&mut self, hallway_options: HallwayOptions) -> &mut DungeonBuilder {
+    pub fn with_hallway_options(&mut self, hallway_options: HallwayOptions<T>) -> &mut DungeonBuilder<T> {
         self.hallway_options = Some(hallway_options);
         self
     }
 
-    pub fn build(&mut self) -> Dungeon {
-        let mut hallway_builder = HallwayBuilder::new();
+    pub fn with_loop_options(&mut self, loop_options: LoopOptions<T>) -> &mut DungeonBuilder<T> {
+        self.loop_options = Some(loop_options);
+        self
+    }
+
+    pub fn with_connectivity_options(&mut self, connectivity_options: ConnectivityOptions<T>) -> &mut DungeonBuilder<T> {
+        self.connectivity_options = Some(connectivity_options);
+        self
+    }
+
+    /// Joins what the connectivity options cannot, like separate areas, with winding
+    /// corridors through the empty space around them.
+    pub fn with_corridor_options(&mut self, corridor_options: CorridorOptions<T>) -> &mut DungeonBuilder<T> {
+        self.corridor_options = Some(corridor_options);
+        self
+    }
+
+    pub fn with_role_options(&mut self, role_options: RoleOptions<T>) -> &mut DungeonBuilder<T> {
+        self.role_options = Some(role_options);
+        self
+    }
+
+    pub fn with_spawn_options(&mut self, spawn_options: SpawnOptions<T>) -> &mut DungeonBuilder<T> {
+        self.spawn_options = Some(spawn_options);
+        self
+    }
+
+    /// Stands walls of the given thickness along every boundary, leaving the doors open.
+    pub fn with_wall_options(&mut self, wall_options: WallOptions<T>) -> &mut DungeonBuilder<T> {
+        self.wall_options = Some(wall_options);
+        self
+    }
+
+    /// Chooses how chunks are cut. Chunks alternate direction and are cut anywhere by default.
+    pub fn with_split_strategy<S: SplitStrategy<T> + 'static>(&mut self, split_strategy: S) -> &mut DungeonBuilder<T> {
+        self.split_strategy = Box::new(split_strategy);
+        self
+    }
+
+    /// Chooses which waiting chunk is split next. The largest goes first by default.
+    pub fn with_split_order(&mut self, split_order: SplitOrder) -> &mut DungeonBuilder<T> {
+        self.split_order = split_order;
+        self
+    }
+
+    /// Registers a hand made room. A chunk that fits one of the registered prefabs is kept
+    /// whole and stamped with it once it cannot be split any more or is at most four times
+    /// the footprint, until the prefab has been placed `count` times.
+    pub fn with_prefab(&mut self, prefab: Prefab<T>) -> &mut DungeonBuilder<T> {
+        self.prefabs.push(prefab);
+        self
+    }
+
+    /// Panics when `try_build` would return an error.
+    pub fn build(&mut self) -> Dungeon<T> {
+        match self.try_build() {
+            Ok(dungeon) => dungeon,
+            Err(error) => panic!("cannot build dungeon: {:?}", error)
+        }
+    }
+
+    /// Builds like `build`, but checks the options first and reports what cannot be laid
+    /// out instead of panicking.
+    pub fn try_build(&mut self) -> Result<Dungeon<T>, BuildError> {
+        self.check()?;
+        Ok(self.generate())
+    }
+
+    fn check(&self) -> Result<(), BuildError> {
+        if self.dimension_options.is_none() {
+            return Err(BuildError::MissingDimensionOptions);
+        }
+        for (idx, room) in self.fixed_rooms.iter().enumerate() {
+            if !self.areas.iter().any(|area| area.contains_rect(room)) {
+                return Err(BuildError::FixedRoomOutsideArea(idx));
+            }
+            let overlaps_fixed = self.fixed_rooms[..idx].iter().any(|other| other.intersects(room));
+            if overlaps_fixed || self.keep_out.iter().any(|zone| zone.intersects(room)) {
+                return Err(BuildError::FixedRoomOverlaps(idx));
+            }
+        }
+        Ok(())
+    }
+
+    fn generate(&mut self) -> Dungeon<T> {
+        if let Some(seed) = self.seed {
+            self.rng = Isaac64Rng::from_seed(&[seed]);
+        }
+        let mut hallway_builder : HallwayBuilder<T> = HallwayBuilder::new();
         let mut dungeon = Dungeon::new();
-        match self.dimension_options {
-            Some(ref dimension_options) => {
-                while self.chunks.len() > 0 {
-                    let mut chunk = self.chunks.pop().expect("chunk not found in queue");
-                    let new_chunk_option = chunk.split(&dimension_options, &mut self.rng);
-                    match new_chunk_option {
-                        Some(new_chunk) => {
-                            match self.hallway_options {
-                                Some(ref hallway_options) => {
-                                    let can_strip_hallway = match new_chunk.chunk_split() {
-                                        ChunkSplit::Vertical => {
-                                            chunk.height() > hallway_options.min_hallway_length && chunk.width() > hallway_options.min_hallway_width
-                                        },
-                                        ChunkSplit::Horizontal => { 
-                                            chunk.width() > hallway_options.min_hallway_length && chunk.height() > hallway_options.min_hallway_width
-                                        }
+        let mut chunks = ChunkQueue::new(self.split_order);
+        let mut total_area = T::ZERO;
+        let mut areas = Vec::new();
+        let mut longest = T::ZERO;
+        for area in &self.areas {
+            longest = longest.max(area.width()).max(area.height());
+            let mut chunk_split = ChunkSplit::Horizontal;
+            if self.rng.gen_weighted_bool(2) {
+                chunk_split = ChunkSplit::Vertical;
+            }
+            areas.push(Chunk::new(*area.lower_left(), *area.upper_right(), chunk_split));
+            total_area += area.area();
+            for zone in &self.keep_out {
+                if let Some(kept_out) = area.intersection(zone) {
+                    total_area -= kept_out.area();
+                }
+            }
+        }
+        for area in &self.hallway_areas {
+            hallway_builder.add_chunk(Chunk::new(*area.lower_left(), *area.upper_right(), ChunkSplit::Horizontal));
+            total_area += area.area();
+        }
+        if let Some(ref hallway_options) = self.hallway_options {
+            let budget = total_area.to_f64() * hallway_options.hallway_percent as f64 / 100f64 - hallway_builder.total_area().to_f64();
+            let split_area = areas.iter().fold(0f64, |sum, chunk| sum + chunk.area().to_f64());
+            for chunk in &mut areas {
+                let share = budget.max(0f64) * chunk.area().to_f64() / split_area;
+                chunk.set_hallway_budget(T::from_f64(share));
+            }
+        }
+        for chunk in areas {
+            chunks.push(chunk);
+        }
+        let mut prefabs_left : Vec<usize> = self.prefabs.iter().map(|prefab| prefab.count).collect();
+        let mut fixed_rooms : Vec<Option<RoomId>> = vec![None; self.fixed_rooms.len()];
+        if let Some(ref dimension_options) = self.dimension_options {
+            while let Some(mut chunk) = chunks.pop(&mut self.rng) {
+                if let Some((chunk_split, at)) = anchor_cut(chunk.rect(), self.fixed_rooms.iter().chain(self.keep_out.iter())) {
+                    let new_chunk = chunk.split_at(chunk_split, at);
+                    chunks.push(chunk);
+                    chunks.push(new_chunk);
+                    continue;
+                }
+                if self.keep_out.iter().any(|zone| zone.contains_rect(chunk.rect())) {
+                    continue;
+                }
+                if let Some(idx) = self.fixed_rooms.iter().position(|room| room.contains_rect(chunk.rect())) {
+                    fixed_rooms[idx] = Some(dungeon.add_room(Room::new(chunk)));
+                    continue;
+                }
+                let splittable = chunk.can_split(dimension_options);
+                let reserved = (0..self.prefabs.len())
+                    .filter(|&idx| prefabs_left[idx] > 0 && (!splittable || self.prefabs[idx].is_snug(chunk.rect())))
+                    .map(|idx| (idx, self.prefabs[idx].rotations_in(chunk.rect())))
+                    .find(|(_, rotations)| !rotations.is_empty());
+                if let Some((idx, rotations)) = reserved {
+                    let rotation = rotations[self.rng.gen_range(0, rotations.len())];
+                    let rect = *chunk.rect();
+                    let room_id = dungeon.add_room(Room::new(chunk));
+                    dungeon.add_prefab(PrefabPlacement::new(idx, &self.prefabs[idx], room_id, &rect, rotation));
+                    prefabs_left[idx] -= 1;
+                    continue;
+                }
+                if let Some(ref distribution) = dimension_options.size_distribution {
+                    let fits = dimension_options.max_area.map_or(true, |max_area| chunk.area() <= max_area) && !chunk.is_too_long(dimension_options);
+                    let leaves = dungeon.rooms().len() + chunks.len() + 1;
+                    let spare_area = chunks.area();
+                    let stop_chance = distribution.stop_chance(chunk.area(), &distribution.histogram(dungeon.rooms()), leaves, spare_area, dimension_options.min_area);
+                    if fits && self.rng.gen::<f64>() < stop_chance {
+                        dungeon.add_room(Room::new(chunk));
+                        continue;
+                    }
+                }
+                let new_chunk_option = chunk.split_with(dimension_options, &*self.split_strategy, &mut self.rng);
+                match new_chunk_option {
+                    Some(mut new_chunk) => {
+                        if let Some(ref hallway_options) = self.hallway_options {
+                            let (lower, upper) = match hallway_options.placement {
+                                HallwayPlacement::Lower => (true, false),
+                                HallwayPlacement::Upper => (false, true),
+                                HallwayPlacement::Centered => (true, true),
+                                HallwayPlacement::EitherSide => {
+                                    let lower : bool = self.rng.gen();
+                                    (lower, !lower)
+                                }
+                            };
+                            let side = new_chunk.chunk_split();
+                            let depth = new_chunk.depth() - 1;
+                            let (length, lower_thickness, upper_thickness, min_rest) = match side {
+                                ChunkSplit::Vertical => (chunk.height(), chunk.width(), new_chunk.width(), dimension_options.min_height),
+                                ChunkSplit::Horizontal => (chunk.width(), chunk.height(), new_chunk.height(), dimension_options.min_width)
+                            };
+                            // A split longer than the hallways may be only gets one along its
+                            // start, leaving the rest of the line for the rooms on both sides.
+                            let hallway_length = match hallway_options.max_hallway_length {
+                                Some(max_length) if length > max_length => max_length.min(length - min_rest),
+                                _ => length
+                            };
+                            let can_strip_hallway = hallway_length > hallway_options.min_hallway_length &&
+                                (!lower || lower_thickness > hallway_options.min_hallway_width) &&
+                                (!upper || upper_thickness > hallway_options.min_hallway_width);
+                            let affordable = match hallway_options.budget {
+                                HallwayBudget::Global => {
+                                    let hallway_percent = (hallway_builder.total_area().to_f64() / total_area.to_f64()) * 100f64;
+                                    hallway_percent < hallway_options.hallway_percent as f64
+                                },
+                                HallwayBudget::Spread => {
+                                    // Cutting off the rest of the line takes its share of the budget along.
+                                    let budget = (chunk.hallway_budget() + new_chunk.hallway_budget()).to_f64() * hallway_length.to_f64() / length.to_f64();
+                                    (hallway_options.min_hallway_width * hallway_length).to_f64() <= budget
+                                }
+                            };
+                            if can_strip_hallway && affordable {
+                                if hallway_length < length {
+                                    let (across, at) = match side {
+                                        ChunkSplit::Vertical => (ChunkSplit::Horizontal, chunk.lower_left().y() + hallway_length),
+                                        ChunkSplit::Horizontal => (ChunkSplit::Vertical, chunk.lower_left().x() + hallway_length)
                                     };
-                                    let hallway_percent = (hallway_builder.total_area() / self.total_area) * 100f32;
-                                    if can_strip_hallway && hallway_percent < hallway_options.hallway_percent {
-                                        let hallway_width = self.rng.gen_range(hallway_options.min_hallway_width, hallway_options.max_hallway_width);
-                                        let hallway_chunk = chunk.strip_hallway(new_chunk.chunk_split(), hallway_width);
-                                        hallway_builder.add_chunk(hallway_chunk);
+                                    chunks.push(chunk.split_at(across, at));
+                                    chunks.push(new_chunk.split_at(across, at));
+                                }
+                                let budget = chunk.hallway_budget() + new_chunk.hallway_budget();
+                                let mut hallway_width = hallway_options.hallway_width(depth, hallway_length, longest, &mut self.rng);
+                                if hallway_options.budget == HallwayBudget::Spread {
+                                    hallway_width = hallway_width.min(budget / hallway_length).max(hallway_options.min_hallway_width);
+                                }
+                                let (before, after) = match (lower, upper) {
+                                    (true, false) => (hallway_width, T::ZERO),
+                                    (false, true) => (T::ZERO, hallway_width),
+                                    _ => {
+                                        let half = hallway_width / (T::ONE + T::ONE);
+                                        (half, hallway_width - half)
                                     }
-                                },
-                                _ => {}
+                                };
+                                let hallway_chunk = chunk.strip_hallway_between(&mut new_chunk, side, before, after);
+                                share_hallway_budget(&mut chunk, &mut new_chunk, (budget - hallway_chunk.area()).max(T::ZERO));
+                                hallway_builder.add_chunk(hallway_chunk);
                             }
-
-                            self.chunks.push(chunk);
-                            self.chunks.push(new_chunk);
-                        },
-                        None => {
-                            dungeon.add_room(Room::new(chunk));
                         }
+
+                        chunks.push(chunk);
+                        chunks.push(new_chunk);
+                    },
+                    None => {
+                        dungeon.add_room(Room::new(chunk));
                     }
                 }
-            },
-            None => {
-                panic!("dimension options must be set with 'with_dimension_options'")
             }
         }
+        if let Some(ref dimension_options) = self.dimension_options {
+            if dimension_options.size_distribution.is_some() || dimension_options.max_aspect.is_some() {
+                dungeon.set_size_report(SizeReport::new(dimension_options.size_distribution.as_ref(), dimension_options.max_aspect, dungeon.rooms()));
+            }
+        }
+        for room in fixed_rooms.into_iter().flatten() {
+            dungeon.add_fixed_room(room);
+        }
         let hallways = hallway_builder.merge_hallways();
         dungeon.add_hallways(hallways);
+        if let Some(ref hallway_options) = self.hallway_options {
+            dungeon.place_hallway_doors(hallway_options.door_width);
+        }
+        if let Some(ref connectivity_options) = self.connectivity_options {
+            dungeon.connect_rooms(connectivity_options, &mut self.rng);
+        }
+        if let Some(ref corridor_options) = self.corridor_options {
+            CorridorBuilder::new(corridor_options).connect(&mut dungeon, &self.keep_out, &mut self.rng);
+        }
+        if let Some(ref loop_options) = self.loop_options {
+            dungeon.add_loops(loop_options, &mut self.rng);
+        }
+        if let Some(ref role_options) = self.role_options {
+            dungeon.assign_roles(role_options, &mut self.rng);
+        }
+        if let Some(ref spawn_options) = self.spawn_options {
+            dungeon.place_spawns(spawn_options, &mut self.rng);
+        }
+        if let Some(ref wall_options) = self.wall_options {
+            dungeon.build_walls(wall_options);
+        }
         dungeon
     }
 }
 
+/// A cut along the wall of an anchor that runs through `rect`, so no random split can
+/// later cross the anchor. Anchors covering the whole of `rect` need no cut.
+fn anchor_cut<'a, T: Scalar + 'a, I: Iterator<Item = &'a Rect<T>>>(rect: &Rect<T>, anchors: I) -> Option<(ChunkSplit, T)> {
+    for anchor in anchors {
+        if !rect.intersects(anchor) || anchor.contains_rect(rect) {
+            continue;
+        }
+        if anchor.lower_left().x() > rect.lower_left().x() + T::TOLERANCE {
+            return Some((ChunkSplit::Vertical, anchor.lower_left().x()));
+        } else if anchor.upper_right().x() < rect.upper_right().x() - T::TOLERANCE {
+            return Some((ChunkSplit::Vertical, anchor.upper_right().x()));
+        } else if anchor.lower_left().y() > rect.lower_left().y() + T::TOLERANCE {
+            return Some((ChunkSplit::Horizontal, anchor.lower_left().y()));
+        } else if anchor.upper_right().y() < rect.upper_right().y() - T::TOLERANCE {
+            return Some((ChunkSplit::Horizontal, anchor.upper_right().y()));
+        }
+    }
+    None
+}
+
+impl<T: Scalar> Default for DungeonBuilder<T> {
+    fn default() -> DungeonBuilder<T> {
+        DungeonBuilder::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use point::Point;
     use dimensionoptions::DimensionOptions;
+    use hallwayoptions::{HallwayOptions, HallwayBudget, HallwayPlacement, WidthScaling};
+    use loopoptions::LoopOptions;
+    use connectivityoptions::ConnectivityOptions;
+    use roleoptions::RoleOptions;
+    use roomrole::RoomRole;
+    use prefab::Prefab;
+    use sizedistribution::SizeDistribution;
+    use splitstrategy::{LongestAxisSplit, GoldenRatioSplit, NormalSplit};
+    use splitorder::SplitOrder;
+    use wall::WallKind;
+    use corridoroptions::{CorridorOptions, CorridorStyle};
+    use door::{Region, DoorKind};
+    use roomgraph::RoomGraph;
+    use std::collections::HashSet;
 
     #[test]
     fn test_build() {
@@ -110,4 +442,401 @@ mod tests {
             .with_dimension_options(DimensionOptions::new(5f32,5f32,5f32))
             .build();
     }
+
+    #[test]
+    fn test_build_with_loops() {
+        // Without hallways the rooms are only joined room to room, which leaves the tree
+        // shaped layout the loops are meant to break up.
+        let dungeon = DungeonBuilder::new()
+            .with_seed(3)
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .with_connectivity_options(ConnectivityOptions::new(2f32))
+            .with_loop_options(LoopOptions::new(3, 4, 2f32))
+            .build();
+        assert!(!dungeon.loops().is_empty() && dungeon.loops().len() <= 3);
+        // Loops are added one after another, so each one is checked against the doors
+        // placed before it.
+        let doors = dungeon.doors();
+        for (idx, door) in doors.iter().enumerate().filter(|&(_, door)| door.kind() == DoorKind::Loop) {
+            let graph = RoomGraph::new(dungeon.rooms().len(), dungeon.hallways().len(), &doors[..idx]);
+            let distance = graph.distances(door.from())[graph.node(door.to())];
+            assert!(distance.is_some_and(|distance| distance >= 4));
+        }
+    }
+
+    #[test]
+    fn test_build_connected() {
+        let dungeon = DungeonBuilder::new()
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .with_hallway_options(HallwayOptions::new(1f32, 0f32, 2f32, 4f32))
+            .with_connectivity_options(ConnectivityOptions::new(2f32))
+            .build();
+        assert!(dungeon.is_connected());
+    }
+
+    #[test]
+    fn test_build_with_roles() {
+        let dungeon = DungeonBuilder::new()
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .with_hallway_options(HallwayOptions::new(10f32, 0f32, 2f32, 4f32))
+            .with_connectivity_options(ConnectivityOptions::new(2f32))
+            .with_role_options(RoleOptions::new())
+            .build();
+        assert_eq!(1, dungeon.rooms_with_role(&RoomRole::Entrance).len());
+        assert_eq!(1, dungeon.rooms_with_role(&RoomRole::Boss).len());
+    }
+
+    #[test]
+    fn test_build_integer() {
+        let dungeon = DungeonBuilder::new()
+            .in_area(Point::new(0i32,0i32), Point::new(200i32,200i32))
+            .with_dimension_options(DimensionOptions::new(10i32,10i32,100i32))
+            .with_hallway_options(HallwayOptions::new(10f32, 0i32, 2i32, 4i32))
+            .with_connectivity_options(ConnectivityOptions::new(2i32))
+            .build();
+        assert!(dungeon.is_connected());
+        let area = dungeon.rooms().iter().fold(0i32, |sum, room| sum + room.rect().area());
+        let hallway_area = dungeon.hallways().iter().flat_map(|hallway| hallway.rects()).fold(0i32, |sum, rect| sum + rect.area());
+        assert_eq!(200i32 * 200i32, area + hallway_area);
+        assert!(dungeon.doors().iter().all(|door| door.width() > 0i32));
+    }
+
+    #[test]
+    fn test_build_double() {
+        let offset = 1e7f64;
+        let dungeon = DungeonBuilder::new()
+            .in_area(Point::new(offset, offset), Point::new(offset + 200f64, offset + 200f64))
+            .with_dimension_options(DimensionOptions::new(10f64,10f64,100f64))
+            .with_hallway_options(HallwayOptions::new(10f32, 0f64, 2f64, 4f64))
+            .with_connectivity_options(ConnectivityOptions::new(2f64))
+            .build();
+        assert!(dungeon.is_connected());
+        let first = dungeon.rooms()[0].rect().center();
+        let last = dungeon.rooms()[dungeon.rooms().len() - 1].rect().center();
+        assert!(dungeon.find_path(&first, &last).is_ok());
+    }
+
+    #[test]
+    fn test_build_with_seed() {
+        let build = |seed: u64| {
+            let dungeon = DungeonBuilder::new()
+                .with_seed(seed)
+                .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+                .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+                .with_hallway_options(HallwayOptions::new(15f32, 0f32, 2f32, 4f32))
+                .with_hallway_area(Point::new(0f32,200f32), Point::new(200f32,204f32))
+                .build();
+            dungeon.rooms().iter().map(|room| *room.rect()).collect::<Vec<_>>()
+        };
+        assert_eq!(build(9), build(9));
+        assert!(build(9) != build(10));
+    }
+
+    #[test]
+    fn test_build_twice_with_seed() {
+        let mut builder = DungeonBuilder::new();
+        builder.with_seed(9)
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .with_hallway_options(HallwayOptions::new(15f32, 0f32, 2f32, 4f32))
+            .with_connectivity_options(ConnectivityOptions::new(2f32));
+        let rooms = |dungeon: &Dungeon| dungeon.rooms().iter().map(|room| *room.rect()).collect::<Vec<_>>();
+        let first = builder.build();
+        let second = builder.build();
+        assert_eq!(rooms(&first), rooms(&second));
+        assert_eq!(first.doors().len(), second.doors().len());
+    }
+
+    #[test]
+    fn test_build_with_prefabs() {
+        let mut arena = Prefab::new("arena", 30f32, 20f32, vec![Point::new(15f32, 0f32)]);
+        arena.max_width = Some(60f32);
+        arena.max_height = Some(50f32);
+        arena.count = 2;
+        let dungeon = DungeonBuilder::new()
+            .with_seed(5)
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .with_hallway_options(HallwayOptions::new(15f32, 0f32, 2f32, 4f32))
+            .with_prefab(arena)
+            .build();
+        assert_eq!(2, dungeon.prefabs().len());
+        for placement in dungeon.prefabs() {
+            let room = dungeon.room(placement.room()).rect();
+            assert!(room.contains_rect(placement.rect()));
+            assert!(placement.entries().iter().all(|entry| placement.rect().contains(entry)));
+            assert_eq!(Some(placement.room()), dungeon.prefab_in(placement.room()).map(|placement| placement.room()));
+        }
+        assert!(dungeon.prefabs()[0].room() != dungeon.prefabs()[1].room());
+    }
+
+    #[test]
+    fn test_prefab_without_limits() {
+        let dungeon = DungeonBuilder::new()
+            .with_seed(5)
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .with_hallway_options(HallwayOptions::new(15f32, 0f32, 2f32, 4f32))
+            .with_prefab(Prefab::new("shop", 20f32, 10f32, vec![]))
+            .build();
+        assert!(dungeon.rooms().len() > 1);
+        assert_eq!(1, dungeon.prefabs().len());
+        assert!(dungeon.room(dungeon.prefabs()[0].room()).area() <= 800f32);
+    }
+
+    #[test]
+    fn test_build_around_anchors() {
+        let fixed = Rect::new(Point::new(50f32,50f32), Point::new(80f32,70f32));
+        let keep_out = Rect::new(Point::new(120f32,0f32), Point::new(200f32,40f32));
+        let dungeon = DungeonBuilder::new()
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .with_hallway_options(HallwayOptions::new(15f32, 0f32, 2f32, 4f32))
+            .with_fixed_room(*fixed.lower_left(), *fixed.upper_right())
+            .with_keep_out(*keep_out.lower_left(), *keep_out.upper_right())
+            .build();
+        assert_eq!(1, dungeon.fixed_rooms().len());
+        assert_eq!(fixed, *dungeon.room(dungeon.fixed_rooms()[0]).rect());
+        for id in dungeon.room_ids() {
+            let room = dungeon.room(id);
+            assert!(!room.rect().intersects(&keep_out));
+            assert!(id == dungeon.fixed_rooms()[0] || !room.rect().intersects(&fixed));
+        }
+        for rect in dungeon.hallways().iter().flat_map(|hallway| hallway.rects()) {
+            assert!(!rect.intersects(&keep_out) && !rect.intersects(&fixed));
+        }
+    }
+
+    #[test]
+    fn test_fixed_room_outside_area() {
+        let try_build = |lower_left: Point, upper_right: Point| DungeonBuilder::new()
+            .in_area(Point::new(0f32,0f32), Point::new(100f32,100f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .with_keep_out(Point::new(0f32,0f32), Point::new(20f32,20f32))
+            .with_fixed_room(Point::new(40f32,40f32), Point::new(60f32,60f32))
+            .with_fixed_room(lower_left, upper_right)
+            .try_build()
+            .map(|dungeon| dungeon.fixed_rooms().len());
+        assert_eq!(Err(BuildError::FixedRoomOutsideArea(1)), try_build(Point::new(150f32,150f32), Point::new(170f32,170f32)));
+        assert_eq!(Err(BuildError::FixedRoomOutsideArea(1)), try_build(Point::new(90f32,10f32), Point::new(110f32,30f32)));
+        assert_eq!(Err(BuildError::FixedRoomOverlaps(1)), try_build(Point::new(10f32,10f32), Point::new(30f32,30f32)));
+        assert_eq!(Err(BuildError::FixedRoomOverlaps(1)), try_build(Point::new(50f32,50f32), Point::new(70f32,70f32)));
+        assert_eq!(Ok(2), try_build(Point::new(60f32,10f32), Point::new(80f32,30f32)));
+        assert_eq!(Err(BuildError::MissingDimensionOptions), DungeonBuilder::<f32>::new().try_build().map(|_| ()));
+    }
+
+    #[test]
+    fn test_build_with_room_count() {
+        for seed in 0..5 {
+            let mut dimension_options = DimensionOptions::new(10f32,10f32,100f32);
+            dimension_options.size_distribution = Some(SizeDistribution::with_room_count(20));
+            let dungeon = DungeonBuilder::new()
+                .with_seed(seed)
+                .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+                .with_dimension_options(dimension_options)
+                .with_hallway_options(HallwayOptions::new(15f32, 0f32, 2f32, 4f32))
+                .build();
+            let report = dungeon.size_report().expect("a distribution was set");
+            assert_eq!(20, dungeon.rooms().len());
+            assert_eq!(20, report.target_room_count());
+            assert_eq!(1f64, report.accuracy());
+        }
+    }
+
+    #[test]
+    fn test_build_with_aspect_limit() {
+        let mut dimension_options = DimensionOptions::new(10f32,10f32,100f32);
+        dimension_options.max_aspect = Some(2f32);
+        let dungeon = DungeonBuilder::new()
+            .with_seed(3)
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(dimension_options)
+            .build();
+        let report = dungeon.size_report().expect("an aspect limit was set");
+        assert_eq!(dungeon.rooms().len(), report.room_count());
+        assert!(report.aspect_violations() * 10 < report.room_count());
+    }
+
+    #[test]
+    fn test_build_with_split_strategies() {
+        let build = |builder: &mut DungeonBuilder| {
+            let dungeon = builder.with_seed(4)
+                .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+                .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+                .with_hallway_options(HallwayOptions::new(15f32, 0f32, 2f32, 4f32))
+                .with_connectivity_options(ConnectivityOptions::new(2f32))
+                .build();
+            assert!(dungeon.is_connected());
+            assert!(!dungeon.rooms().is_empty());
+            dungeon.rooms().iter().map(|room| *room.rect()).collect::<Vec<_>>()
+        };
+        let alternating = build(&mut DungeonBuilder::new());
+        let longest_axis = build(DungeonBuilder::new().with_split_strategy(LongestAxisSplit));
+        assert!(alternating != longest_axis);
+        build(DungeonBuilder::new().with_split_strategy(GoldenRatioSplit::new(0.05f64)));
+        build(DungeonBuilder::new().with_split_strategy(NormalSplit::new(0.2f64)));
+    }
+
+    #[test]
+    fn test_longest_axis_keeps_rooms_square() {
+        let dungeon = DungeonBuilder::new()
+            .with_seed(6)
+            .with_split_strategy(LongestAxisSplit)
+            .in_area(Point::new(0i32,0i32), Point::new(300i32,100i32))
+            .with_dimension_options(DimensionOptions::new(8i32,8i32,64i32))
+            .build();
+        assert!(dungeon.rooms().iter().all(|room| room.rect().aspect_ratio() <= 2f64 + 1f64 / 8f64));
+    }
+
+    #[test]
+    fn test_build_in_split_orders() {
+        for &split_order in &[SplitOrder::LargestFirst, SplitOrder::BreadthFirst, SplitOrder::DepthFirst, SplitOrder::Random] {
+            let dungeon = DungeonBuilder::new()
+                .with_seed(2)
+                .with_split_order(split_order)
+                .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+                .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+                .with_hallway_options(HallwayOptions::new(15f32, 0f32, 2f32, 4f32))
+                .with_connectivity_options(ConnectivityOptions::new(2f32))
+                .build();
+            let area = dungeon.rooms().iter().fold(0f32, |sum, room| sum + room.rect().area());
+            assert!(dungeon.is_connected());
+            assert!(area > 30000f32 && area <= 40000f32);
+        }
+    }
+
+    #[test]
+    fn test_spread_hallway_budget() {
+        let served = |budget: HallwayBudget| {
+            let mut hallway_options = HallwayOptions::new(15f32, 0f32, 2f32, 4f32);
+            hallway_options.budget = budget;
+            let dungeon = DungeonBuilder::new()
+                .with_seed(1)
+                .with_split_order(SplitOrder::DepthFirst)
+                .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+                .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+                .with_hallway_options(hallway_options)
+                .build();
+            let hallway_area = dungeon.hallways().iter().flat_map(|hallway| hallway.rects()).fold(0f32, |sum, rect| sum + rect.area());
+            assert!(hallway_area <= 6000f32 + 200f32 * 4f32);
+            let rooms : HashSet<RoomId> = dungeon.doors().iter().flat_map(|door| vec![door.from(), door.to()])
+                .filter_map(|region| match region { Region::Room(room) => Some(room), _ => None })
+                .collect();
+            rooms.len() as f64 / dungeon.rooms().len() as f64
+        };
+        let global = served(HallwayBudget::Global);
+        let spread = served(HallwayBudget::Spread);
+        assert!(spread > 0.8f64, "{} of the rooms open onto a hallway", spread);
+        assert!(spread > global);
+    }
+
+    fn scaled_hallways(width_scaling: WidthScaling, max_hallway_length: Option<i32>) -> Dungeon<i32> {
+        let mut hallway_options = HallwayOptions::new(15f32, 0i32, 2i32, 6i32);
+        hallway_options.width_scaling = width_scaling;
+        hallway_options.max_hallway_length = max_hallway_length;
+        hallway_options.budget = HallwayBudget::Spread;
+        DungeonBuilder::new()
+            .with_seed(8)
+            .in_area(Point::new(0i32,0i32), Point::new(200i32,200i32))
+            .with_dimension_options(DimensionOptions::new(8i32,8i32,64i32))
+            .with_hallway_options(hallway_options)
+            .build()
+    }
+
+    #[test]
+    fn test_hallway_width_scaling() {
+        let widest = |dungeon: &Dungeon<i32>| dungeon.hallways().iter().flat_map(|hallway| hallway.rects())
+            .map(|rect| Ord::min(rect.width(), rect.height())).max().unwrap();
+        assert!(widest(&scaled_hallways(WidthScaling::Uniform, None)) < 6);
+        assert_eq!(6, widest(&scaled_hallways(WidthScaling::Depth(3), None)));
+        assert_eq!(6, widest(&scaled_hallways(WidthScaling::Size, None)));
+    }
+
+    #[test]
+    fn test_max_hallway_length() {
+        let dungeon = scaled_hallways(WidthScaling::Depth(3), Some(60));
+        assert!(!dungeon.hallways().is_empty());
+        let longest = |dungeon: &Dungeon<i32>| dungeon.hallways().iter().flat_map(|hallway| hallway.rects())
+            .map(|rect| Ord::max(rect.width(), rect.height())).max().unwrap();
+        assert!(longest(&scaled_hallways(WidthScaling::Depth(3), None)) > 60);
+        // The first split crosses the whole area, far longer than the limit, and still gets a
+        // hallway starting at the edge of the area.
+        assert!(dungeon.hallways().iter().flat_map(|hallway| hallway.rects())
+            .any(|rect| Ord::max(rect.width(), rect.height()) == 60 && Ord::min(rect.lower_left().x(), rect.lower_left().y()) == 0));
+        for hallway in dungeon.hallways() {
+            for (start, end) in hallway.edges() {
+                assert!((end.x() - start.x()).abs() + (end.y() - start.y()).abs() <= 60 + 6 + 6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hallway_placement() {
+        for &placement in &[HallwayPlacement::Lower, HallwayPlacement::Upper, HallwayPlacement::Centered, HallwayPlacement::EitherSide] {
+            let mut hallway_options = HallwayOptions::new(15f32, 0i32, 2i32, 5i32);
+            hallway_options.placement = placement;
+            let dungeon = DungeonBuilder::new()
+                .with_seed(12)
+                .in_area(Point::new(0i32,0i32), Point::new(200i32,200i32))
+                .with_dimension_options(DimensionOptions::new(8i32,8i32,64i32))
+                .with_hallway_options(hallway_options)
+                .with_connectivity_options(ConnectivityOptions::new(2i32))
+                .build();
+            let hallways : Vec<Rect<i32>> = dungeon.hallways().iter().flat_map(|hallway| hallway.rects()).collect();
+            let area = dungeon.rooms().iter().map(|room| room.rect().area()).sum::<i32>() + hallways.iter().map(|rect| rect.area()).sum::<i32>();
+            assert_eq!(40000, area, "{:?} leaves gaps or overlaps", placement);
+            assert!(dungeon.rooms().iter().all(|room| !hallways.iter().any(|hallway| hallway.intersects(room.rect()))));
+            assert!(dungeon.is_connected());
+        }
+    }
+
+    #[test]
+    fn test_build_with_corridors() {
+        let build = |corridors: bool| {
+            let mut builder = DungeonBuilder::new();
+            builder.with_seed(3)
+                .in_area(Point::new(0f32,0f32), Point::new(100f32,100f32))
+                .in_area(Point::new(150f32,120f32), Point::new(250f32,220f32))
+                .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+                .with_hallway_options(HallwayOptions::new(15f32, 0f32, 2f32, 4f32))
+                .with_connectivity_options(ConnectivityOptions::new(2f32));
+            if corridors {
+                let mut corridor_options = CorridorOptions::new(3f32, CorridorStyle::Noise);
+                corridor_options.diagonals = true;
+                builder.with_corridor_options(corridor_options);
+            }
+            builder.build()
+        };
+        assert!(!build(false).is_connected());
+        let dungeon = build(true);
+        assert!(dungeon.is_connected());
+        assert_eq!(1, dungeon.doors().iter().filter(|door| door.kind() == DoorKind::Corridor).count() / 2);
+        assert!(dungeon.find_path(&Point::new(50f32, 50f32), &Point::new(200f32, 170f32)).is_ok());
+    }
+
+    #[test]
+    fn test_build_with_walls() {
+        let dungeon = DungeonBuilder::new()
+            .with_seed(4)
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,400f32))
+            .with_hallway_options(HallwayOptions::new(15f32, 0f32, 2f32, 4f32))
+            .with_connectivity_options(ConnectivityOptions::new(2f32))
+            .with_loop_options(LoopOptions::new(3, 4, 2f32))
+            .with_wall_options(WallOptions::new(1f32))
+            .build();
+        let walls = dungeon.walls();
+        assert!(walls.iter().any(|wall| wall.kind() == WallKind::Interior));
+        assert!(walls.iter().any(|wall| wall.kind() == WallKind::Exterior));
+        for (idx, wall) in walls.iter().enumerate() {
+            assert!(walls[idx + 1..].iter().all(|other| !wall.bounds().intersects(&other.bounds())));
+        }
+        for door in dungeon.doors() {
+            let middle = Point::new((door.start().x() + door.end().x()) / 2f32, (door.start().y() + door.end().y()) / 2f32);
+            assert!(walls.iter().all(|wall| !wall.bounds().contains(&middle)));
+        }
+    }
 }
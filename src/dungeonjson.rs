@@ -0,0 +1,268 @@
+use dungeon::Dungeon;
+use room::{Room, RoomId};
+use hallway::Hallway;
+use door::{Door, DoorKind, Region};
+use spawnpoint::SpawnPoint;
+use roomrole::RoomRole;
+use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+use json::{self, Json};
+
+/// Why `Dungeon::from_json` could not read a layout.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ImportError {
+    /// The text is not JSON. Holds the byte offset where reading stopped.
+    Syntax(usize),
+    /// A field is missing or holds the wrong kind of value. Holds its path, like
+    /// `rooms[2].lower_left`.
+    Field(String),
+    /// A door or spawn point is in a room or hallway the layout does not have. Holds the
+    /// path to the region.
+    Region(String),
+}
+
+/// Writes the layout as
+///
+/// ```text
+/// {"rooms":[{"id":0,"lower_left":[0,0],"upper_right":[10,8],"roles":["boss"]}],
+///  "hallways":[{"points":[[10,0],[10,2],[30,2],[30,0]],"holes":[]}],
+///  "doors":[{"from":"room 0","to":"hallway 0","start":[10,0],"end":[10,2],"kind":"hallway"}],
+///  "spawn_points":[{"position":[4,4],"kind":"loot","region":"room 0"}]}
+/// ```
+pub fn write<T: Scalar>(dungeon: &Dungeon<T>) -> String {
+    let rooms : Vec<String> = dungeon.room_ids().map(|id| {
+        let room = dungeon.room(id);
+        let roles : Vec<String> = room.roles().iter().map(|role| json::quote(role.name())).collect();
+        format!("{{\"id\":{},\"lower_left\":{},\"upper_right\":{},\"roles\":[{}]}}", id.index(), point(room.lower_left()), point(room.upper_right()), roles.join(","))
+    }).collect();
+    let hallways : Vec<String> = dungeon.hallways().iter().map(|hallway| {
+        let holes : Vec<String> = hallway.holes().iter().map(|hole| points(hole)).collect();
+        format!("{{\"points\":{},\"holes\":[{}]}}", points(hallway.points()), holes.join(","))
+    }).collect();
+    let doors : Vec<String> = dungeon.doors().iter().map(|door| {
+        format!("{{\"from\":\"{}\",\"to\":\"{}\",\"start\":{},\"end\":{},\"kind\":\"{}\"}}", door.from(), door.to(), point(door.start()), point(door.end()), door.kind().name())
+    }).collect();
+    let spawn_points : Vec<String> = dungeon.spawn_points().iter().map(|spawn_point| {
+        format!("{{\"position\":{},\"kind\":{},\"region\":\"{}\"}}", point(spawn_point.position()), json::quote(spawn_point.kind()), spawn_point.region())
+    }).collect();
+    format!("{{\"rooms\":[{}],\"hallways\":[{}],\"doors\":[{}],\"spawn_points\":[{}]}}", rooms.join(","), hallways.join(","), doors.join(","), spawn_points.join(","))
+}
+
+/// Reads a layout in the form `write` produces. Any of the four lists may be left out,
+/// and so may the roles of a room and the holes of a hallway.
+///
+/// Doors and spawn points name rooms by the `id` written with them, falling back to
+/// their place in the list when it is left out, so rooms can be deleted by hand without
+/// renumbering the rest. The rooms read back get fresh ids in list order.
+pub fn read<T: Scalar>(text: &str) -> Result<Dungeon<T>, ImportError> {
+    let document = json::parse(text).map_err(ImportError::Syntax)?;
+    let mut dungeon = Dungeon::new();
+    let mut room_ids = Vec::new();
+    for (idx, value) in list(&document, "rooms", "")?.iter().enumerate() {
+        let path = format!("rooms[{}]", idx);
+        let id = match value.get("id") {
+            None => idx,
+            Some(id) => match id.as_f64() {
+                Some(id) if id >= 0f64 && id.fract() == 0f64 && !room_ids.contains(&(id as usize)) => id as usize,
+                _ => return Err(ImportError::Field(format!("{}.id", path)))
+            }
+        };
+        room_ids.push(id);
+        let lower_left = read_point(field(value, "lower_left", &path)?, &format!("{}.lower_left", path))?;
+        let upper_right = read_point(field(value, "upper_right", &path)?, &format!("{}.upper_right", path))?;
+        let mut room = Room::from_rect(Rect::new(lower_left, upper_right));
+        for (role_idx, role) in list(value, "roles", &path)?.iter().enumerate() {
+            room.add_role(read_role(text_of(role, &format!("{}.roles[{}]", path, role_idx))?));
+        }
+        dungeon.add_room(room);
+    }
+    let mut hallways = Vec::new();
+    for (idx, value) in list(&document, "hallways", "")?.iter().enumerate() {
+        let path = format!("hallways[{}]", idx);
+        let outline = read_points(field(value, "points", &path)?, &format!("{}.points", path))?;
+        let holes = list(value, "holes", &path)?.iter().enumerate()
+            .map(|(hole_idx, hole)| read_points(hole, &format!("{}.holes[{}]", path, hole_idx)))
+            .collect::<Result<Vec<Vec<Point<T>>>, ImportError>>()?;
+        hallways.push(Hallway::with_holes(outline, holes));
+    }
+    dungeon.add_hallways(hallways);
+    for (idx, value) in list(&document, "doors", "")?.iter().enumerate() {
+        let path = format!("doors[{}]", idx);
+        let from = read_region(&room_ids, dungeon.hallways().len(), field(value, "from", &path)?, &format!("{}.from", path))?;
+        let to = read_region(&room_ids, dungeon.hallways().len(), field(value, "to", &path)?, &format!("{}.to", path))?;
+        let start = read_point(field(value, "start", &path)?, &format!("{}.start", path))?;
+        let end = read_point(field(value, "end", &path)?, &format!("{}.end", path))?;
+        let kind_path = format!("{}.kind", path);
+        let kind = match text_of(field(value, "kind", &path)?, &kind_path)? {
+            "hallway" => DoorKind::Hallway,
+            "loop" => DoorKind::Loop,
+            "connectivity" => DoorKind::Connectivity,
+            "corridor" => DoorKind::Corridor,
+            _ => return Err(ImportError::Field(kind_path))
+        };
+        dungeon.add_door(Door::new(from, to, start, end, kind));
+    }
+    for (idx, value) in list(&document, "spawn_points", "")?.iter().enumerate() {
+        let path = format!("spawn_points[{}]", idx);
+        let position = read_point(field(value, "position", &path)?, &format!("{}.position", path))?;
+        let kind = text_of(field(value, "kind", &path)?, &format!("{}.kind", path))?;
+        let region = read_region(&room_ids, dungeon.hallways().len(), field(value, "region", &path)?, &format!("{}.region", path))?;
+        dungeon.add_spawn_point(SpawnPoint::new(position, kind.to_string(), region));
+    }
+    Ok(dungeon)
+}
+
+fn point<T: Scalar>(point: &Point<T>) -> String {
+    format!("[{},{}]", point.x(), point.y())
+}
+
+fn points<T: Scalar>(points: &[Point<T>]) -> String {
+    let points : Vec<String> = points.iter().map(self::point).collect();
+    format!("[{}]", points.join(","))
+}
+
+fn field<'a>(value: &'a Json, key: &str, path: &str) -> Result<&'a Json, ImportError> {
+    value.get(key).ok_or_else(|| ImportError::Field(join(path, key)))
+}
+
+/// The array under `key`, or nothing when the key is left out.
+fn list<'a>(value: &'a Json, key: &str, path: &str) -> Result<&'a [Json], ImportError> {
+    match value.get(key) {
+        None => Ok(&[]),
+        Some(items) => items.as_array().ok_or_else(|| ImportError::Field(join(path, key)))
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) }
+}
+
+fn text_of<'a>(value: &'a Json, path: &str) -> Result<&'a str, ImportError> {
+    value.as_str().ok_or_else(|| ImportError::Field(path.to_string()))
+}
+
+fn read_point<T: Scalar>(value: &Json, path: &str) -> Result<Point<T>, ImportError> {
+    match value.as_array() {
+        Some(coordinates) if coordinates.len() == 2 => match (coordinates[0].as_f64(), coordinates[1].as_f64()) {
+            (Some(x), Some(y)) => Ok(Point::new(T::from_f64(x), T::from_f64(y))),
+            _ => Err(ImportError::Field(path.to_string()))
+        },
+        _ => Err(ImportError::Field(path.to_string()))
+    }
+}
+
+fn read_points<T: Scalar>(value: &Json, path: &str) -> Result<Vec<Point<T>>, ImportError> {
+    let points = value.as_array().ok_or_else(|| ImportError::Field(path.to_string()))?;
+    points.iter().enumerate().map(|(idx, point)| read_point(point, &format!("{}[{}]", path, idx))).collect()
+}
+
+fn read_role(name: &str) -> RoomRole {
+    match name {
+        "entrance" => RoomRole::Entrance,
+        "exit" => RoomRole::Exit,
+        "boss" => RoomRole::Boss,
+        "treasure" => RoomRole::Treasure,
+        "dead_end" => RoomRole::DeadEnd,
+        tag => RoomRole::Tag(tag.to_string())
+    }
+}
+
+/// A region written like `room 3` or `hallway 0`, where rooms go by the ids in
+/// `room_ids` and hallways by their place in the list.
+fn read_region(room_ids: &[usize], hallway_count: usize, value: &Json, path: &str) -> Result<Region, ImportError> {
+    let text = text_of(value, path)?;
+    let mut words = text.split(' ');
+    match (words.next(), words.next().and_then(|idx| idx.parse::<usize>().ok()), words.next()) {
+        (Some("room"), Some(id), None) => room_ids.iter().position(|&room_id| room_id == id)
+            .map(|idx| Region::Room(RoomId::new(idx)))
+            .ok_or_else(|| ImportError::Region(path.to_string())),
+        (Some("hallway"), Some(idx), None) if idx < hallway_count => Ok(Region::Hallway(idx)),
+        (Some("hallway"), Some(_), None) => Err(ImportError::Region(path.to_string())),
+        _ => Err(ImportError::Field(path.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dungeonbuilder::DungeonBuilder;
+    use dimensionoptions::DimensionOptions;
+    use hallwayoptions::HallwayOptions;
+    use connectivityoptions::ConnectivityOptions;
+    use roleoptions::RoleOptions;
+    use spawnoptions::{SpawnOptions, SpawnDensity, SpawnTarget};
+
+    #[test]
+    fn test_read_hand_written() {
+        let dungeon : Dungeon<i32> = read("{
+            \"rooms\": [{\"lower_left\": [0, 0], \"upper_right\": [10, 8], \"roles\": [\"boss\", \"armory\"]}],
+            \"hallways\": [{\"points\": [[10, 0], [10, 2], [30, 2], [30, 0]]}],
+            \"doors\": [{\"from\": \"room 0\", \"to\": \"hallway 0\", \"start\": [10, 0], \"end\": [10, 2], \"kind\": \"hallway\"}]
+        }").unwrap();
+        assert_eq!(1, dungeon.rooms().len());
+        assert_eq!(vec![RoomRole::Boss, RoomRole::Tag("armory".to_string())], dungeon.rooms()[0].roles().to_vec());
+        assert_eq!(4, dungeon.hallways()[0].points().len());
+        assert!(dungeon.doors()[0].connects(Region::Room(RoomId::new(0)), Region::Hallway(0)));
+        assert!(dungeon.is_connected());
+    }
+
+    #[test]
+    fn test_read_room_ids() {
+        let dungeon : Dungeon<i32> = read("{
+            \"rooms\": [{\"id\": 4, \"lower_left\": [0, 0], \"upper_right\": [10, 10]}, {\"id\": 2, \"lower_left\": [10, 0], \"upper_right\": [20, 10]}],
+            \"doors\": [{\"from\": \"room 2\", \"to\": \"room 4\", \"start\": [10, 4], \"end\": [10, 6], \"kind\": \"loop\"}]
+        }").unwrap();
+        assert_eq!(Region::Room(RoomId::new(1)), dungeon.doors()[0].from());
+        assert_eq!(Region::Room(RoomId::new(0)), dungeon.doors()[0].to());
+        assert!(write(&dungeon).starts_with("{\"rooms\":[{\"id\":0,"));
+        assert_eq!(Err(ImportError::Field("rooms[1].id".to_string())),
+            read::<i32>("{\"rooms\":[{\"id\":1,\"lower_left\":[0,0],\"upper_right\":[1,1]},{\"id\":1,\"lower_left\":[1,0],\"upper_right\":[2,1]}]}").map(|_| ()));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut spawn_options = SpawnOptions::new(4f32, 1f32, 2f32);
+        spawn_options.densities.push(SpawnDensity::new("loot \"rare\"", SpawnTarget::Room, 0.01f32));
+        let dungeon = DungeonBuilder::new()
+            .with_seed(6)
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,400f32))
+            .with_hallway_options(HallwayOptions::new(20f32, 0f32, 2f32, 4f32))
+            .with_connectivity_options(ConnectivityOptions::new(2f32))
+            .with_role_options(RoleOptions::new())
+            .with_spawn_options(spawn_options)
+            .build();
+        let json = write(&dungeon);
+        let read : Dungeon<f32> = read(&json).unwrap();
+        assert_eq!(json, write(&read));
+        assert_eq!(dungeon.rooms().len(), read.rooms().len());
+        assert!(!read.spawn_points().is_empty());
+        assert!(read.find_path(&dungeon.rooms()[0].rect().center(), &dungeon.rooms()[1].rect().center()).is_ok());
+    }
+
+    #[test]
+    fn test_merge_and_place_doors() {
+        let mut dungeon : Dungeon<i32> = read("{
+            \"rooms\": [{\"lower_left\": [0, 0], \"upper_right\": [10, 10]}, {\"lower_left\": [0, 12], \"upper_right\": [10, 20]}],
+            \"hallways\": [{\"points\": [[10, 0], [10, 20], [12, 20], [12, 0]]}, {\"points\": [[0, 10], [0, 12], [11, 12], [11, 10]]}]
+        }").unwrap();
+        dungeon.merge_hallways();
+        assert_eq!(1, dungeon.hallways().len());
+        dungeon.place_hallway_doors(2);
+        assert_eq!(2, dungeon.doors().len());
+        assert!(dungeon.is_connected());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Err(ImportError::Syntax(9)), read::<f32>("{\"rooms\":}").map(|_| ()));
+        let deep = format!("{{\"rooms\":{}", "[".repeat(200_000));
+        // The document object is the first level, so the 128th array is the one refused.
+        assert_eq!(Err(ImportError::Syntax(9 + 127)), read::<f32>(&deep).map(|_| ()));
+        assert_eq!(Err(ImportError::Field("rooms[0].upper_right".to_string())), read::<f32>("{\"rooms\":[{\"lower_left\":[0,0]}]}").map(|_| ()));
+        assert_eq!(Err(ImportError::Field("hallways[0].points[1]".to_string())), read::<f32>("{\"hallways\":[{\"points\":[[0,0],[1]]}]}").map(|_| ()));
+        assert_eq!(Err(ImportError::Region("doors[0].to".to_string())),
+            read::<f32>("{\"rooms\":[{\"lower_left\":[0,0],\"upper_right\":[1,1]}],\"doors\":[{\"from\":\"room 0\",\"to\":\"room 1\",\"start\":[1,0],\"end\":[1,1],\"kind\":\"loop\"}]}").map(|_| ()));
+    }
+}
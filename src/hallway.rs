@@ -1,17 +1,69 @@
 use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+use segment;
 
-pub struct Hallway {
-    points: Vec<Point>,
+pub struct Hallway<T = f32> {
+    points: Vec<Point<T>>,
+    holes: Vec<Vec<Point<T>>>,
+    rects: Vec<Rect<T>>,
 }
 
-impl Hallway {
-    pub fn new(points: Vec<Point>) -> Hallway {
-        Hallway { 
-            points: points, 
+impl<T: Scalar> Hallway<T> {
+    pub fn new(points: Vec<Point<T>>) -> Hallway<T> {
+        Hallway {
+            points,
+            holes: Vec::new(),
+            rects: Vec::new(),
         }
     }
 
-    pub fn points(&self) -> &Vec<Point> {
+    pub fn with_holes(points: Vec<Point<T>>, holes: Vec<Vec<Point<T>>>) -> Hallway<T> {
+        Hallway {
+            points,
+            holes,
+            rects: Vec::new(),
+        }
+    }
+
+    /// A hallway whose outline is not axis aligned, with `rects` standing in for it
+    /// wherever rectangles are needed.
+    pub fn with_rects(points: Vec<Point<T>>, rects: Vec<Rect<T>>) -> Hallway<T> {
+        Hallway {
+            points,
+            holes: Vec::new(),
+            rects,
+        }
+    }
+
+    pub fn points(&self) -> &Vec<Point<T>> {
         &self.points
     }
+
+    /// Outlines of the areas enclosed by the hallway that are not part of it.
+    pub fn holes(&self) -> &[Vec<Point<T>>] {
+        &self.holes[..]
+    }
+
+    pub fn contains(&self, point: &Point<T>) -> bool {
+        segment::polygon_contains(&self.points, point) && !self.holes.iter().any(|hole| segment::polygon_contains(hole, point))
+    }
+
+    /// Every wall of the hallway, including the walls around its holes.
+    pub fn edges(&self) -> Vec<(Point<T>, Point<T>)> {
+        let mut edges = segment::polygon_edges(&self.points);
+        for hole in &self.holes {
+            edges.extend(segment::polygon_edges(hole));
+        }
+        edges
+    }
+
+    /// Splits the hallway into non-overlapping rectangles. Hallways with diagonal walls
+    /// return the rectangles they were made with, which only approximate the outline.
+    pub fn rects(&self) -> Vec<Rect<T>> {
+        if !self.rects.is_empty() {
+            return self.rects.clone();
+        }
+        segment::decompose(&self.edges()).into_iter().map(|(lower_left, upper_right)| Rect::new(lower_left, upper_right)).collect()
+    }
 }
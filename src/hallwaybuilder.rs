@@ -1,83 +1,232 @@
 use point::Point;
+use rect::Rect;
 use chunk::Chunk;
 use hallway::Hallway;
-use std::collections::HashMap;
+use scalar::Scalar;
+use segment;
+use std::collections::BTreeMap;
 
+type Vertex = (usize, usize);
 
-pub struct HallwayBuilder {
-    points: Vec<Point>,
-    total_area: f32
+/// How far off a hole edge to test which outline encloses the hole.
+const HOLE_PROBE: f64 = 0.001;
+
+pub struct HallwayBuilder<T = f32> {
+    chunks: Vec<Rect<T>>,
+    total_area: T
 }
 
-impl HallwayBuilder  {
-    pub fn new() -> HallwayBuilder  {
-        HallwayBuilder { 
-            points: Vec::new(), 
-            total_area: 0f32
+impl<T: Scalar> HallwayBuilder<T>  {
+    pub fn new() -> HallwayBuilder<T>  {
+        HallwayBuilder {
+            chunks: Vec::new(),
+            total_area: T::ZERO
         }
     }
 
-    pub fn total_area(&self) -> f32 {
+    pub fn total_area(&self) -> T {
         self.total_area
     }
 
-    pub fn add_chunk(&mut self, chunk: Chunk) -> &mut HallwayBuilder {
-        self.total_area = self.total_area + chunk.area();
-        self.points.push(chunk.lower_left().clone());
-        self.points.push(Point::new(chunk.lower_left().x(), chunk.upper_right().y()));
-        self.points.push(chunk.upper_right().clone());
-        self.points.push(Point::new(chunk.upper_right().x(), chunk.lower_left().y()));
+    pub fn add_chunk(&mut self, chunk: Chunk<T>) -> &mut HallwayBuilder<T> {
+        self.total_area += chunk.area();
+        self.chunks.push(*chunk.rect());
         self
     }
 
-    pub fn merge_hallways(&mut self) -> Vec<Hallway> {
-        let mut hallways : Vec<Hallway> = Vec::new();
-        self.points.sort_by(|a, b| a.compare_x_y(&b));
-        self.points.dedup();
-        let mut horizontal_edges : HashMap<u64, Point> = HashMap::with_capacity(self.points.len());
-        let mut vertical_edges : HashMap<u64, Point> = HashMap::with_capacity(self.points.len());
-        let mut idx = 0usize;
-        while idx < self.points.len() - 1 {
-            vertical_edges.insert(self.points[idx].hash(), self.points[idx+1].clone());
-            vertical_edges.insert(self.points[idx+1].hash(), self.points[idx].clone());
-            idx = idx + 2;
+    pub fn add_rect(&mut self, rect: Rect<T>) -> &mut HallwayBuilder<T> {
+        self.total_area += rect.area();
+        self.chunks.push(rect);
+        self
+    }
+
+    /// Joins the stripped chunks into hallway outlines. The chunks are laid on a grid made
+    /// from their own coordinates, the border of the covered cells is walked with the
+    /// hallway on the left, and loops walked clockwise become holes of the hallway
+    /// around them.
+    pub fn merge_hallways(&mut self) -> Vec<Hallway<T>> {
+        if self.chunks.is_empty() {
+            return Vec::new();
+        }
+        let xs = coordinates(self.chunks.iter().flat_map(|chunk| vec![chunk.lower_left().x(), chunk.upper_right().x()]).collect());
+        let ys = coordinates(self.chunks.iter().flat_map(|chunk| vec![chunk.lower_left().y(), chunk.upper_right().y()]).collect());
+        let columns = xs.len() - 1;
+        let rows = ys.len() - 1;
+        let mut covered = vec![false; columns * rows];
+        for chunk in &self.chunks {
+            for column in index(&xs, chunk.lower_left().x())..index(&xs, chunk.upper_right().x()) {
+                for row in index(&ys, chunk.lower_left().y())..index(&ys, chunk.upper_right().y()) {
+                    covered[row * columns + column] = true;
+                }
+            }
+        }
+        let is_covered = |column: usize, row: usize| covered[row * columns + column];
+
+        let mut edges : BTreeMap<Vertex, Vec<Vertex>> = BTreeMap::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                if !is_covered(column, row) {
+                    continue;
+                }
+                if row == 0 || !is_covered(column, row - 1) {
+                    edges.entry((column, row)).or_default().push((column + 1, row));
+                }
+                if column + 1 == columns || !is_covered(column + 1, row) {
+                    edges.entry((column + 1, row)).or_default().push((column + 1, row + 1));
+                }
+                if row + 1 == rows || !is_covered(column, row + 1) {
+                    edges.entry((column + 1, row + 1)).or_default().push((column, row + 1));
+                }
+                if column == 0 || !is_covered(column - 1, row) {
+                    edges.entry((column, row + 1)).or_default().push((column, row));
+                }
+            }
         }
-        self.points.sort_by(|a, b| a.compare_y_x(&b));
-        idx = 0usize;
-        while idx < self.points.len() - 1 {
-            horizontal_edges.insert(self.points[idx].hash(), self.points[idx+1].clone());
-            horizontal_edges.insert(self.points[idx+1].hash(), self.points[idx].clone());
-            idx = idx + 2;
+
+        let mut outlines : Vec<Vec<Point<T>>> = Vec::new();
+        let mut holes : Vec<Vec<Point<T>>> = Vec::new();
+        while let Some(start) = edges.keys().next().cloned() {
+            let outline = walk(&mut edges, start);
+            let points : Vec<Point<T>> = outline.iter().map(|&(column, row)| Point::new(xs[column], ys[row])).collect();
+            if signed_area(&points) > T::ZERO {
+                outlines.push(points);
+            } else {
+                holes.push(points);
+            }
         }
-        let mut length = vertical_edges.len();
-        while length > 1 {
-            let mut points : Vec<Point> = Vec::new();
-            points.push(vertical_edges.values().nth(0).expect("No points in vertical_edges").clone()); 
-            let mut current_hash = points[0].hash();
-            let first_hash = current_hash;
-            let point = horizontal_edges.remove(&current_hash).expect("Start Horizontal Point not found");
-            let mut next_hash = point.hash();
-            horizontal_edges.remove(&next_hash);
-            points.push(point);
-            current_hash = next_hash;
-            next_hash = vertical_edges.get(&current_hash).expect("Next Vertical Point not found").hash();
-            while first_hash != next_hash {
-                let point = vertical_edges.remove(&current_hash).unwrap();
-                vertical_edges.remove(&point.hash());
-                points.push(point);
-                current_hash = next_hash;
-                let point = horizontal_edges.remove(&current_hash).expect("Horizontal Point not found");
-                next_hash = point.hash();
-                horizontal_edges.remove(&next_hash);
-                points.push(point);
-                current_hash = next_hash;
-                next_hash = vertical_edges.get(&current_hash).expect("Vertical Point not found").hash();
+
+        let mut hallway_holes : Vec<Vec<Vec<Point<T>>>> = vec![Vec::new(); outlines.len()];
+        for hole in holes {
+            // The hallway lies on the left of every hole edge, so a point just left of the
+            // middle of the first edge tells which outline the hole belongs to. The probe
+            // is worked out in f64 so it lands off the grid on integer maps as well.
+            let (start, end) = (hole[0].cast::<f64>(), hole[1].cast::<f64>());
+            let length = start.distance(&end);
+            let inside = Point::new(
+                (start.x() + end.x()) / 2f64 - (end.y() - start.y()) / length * HOLE_PROBE,
+                (start.y() + end.y()) / 2f64 + (end.x() - start.x()) / length * HOLE_PROBE
+            );
+            let owner = outlines.iter().position(|outline| {
+                let outline : Vec<Point<f64>> = outline.iter().map(|point| point.cast()).collect();
+                segment::polygon_contains(&outline, &inside)
+            });
+            if let Some(idx) = owner {
+                hallway_holes[idx].push(hole);
             }
-            let point = vertical_edges.remove(&current_hash).unwrap();
-            vertical_edges.remove(&point.hash());
-            hallways.push(Hallway::new(points));
-            length = vertical_edges.len();
         }
-        hallways
+        outlines.into_iter().zip(hallway_holes).map(|(points, holes)| Hallway::with_holes(points, holes)).collect()
+    }
+}
+
+fn coordinates<T: Scalar>(mut values: Vec<T>) -> Vec<T> {
+    values.sort_by(|a, b| a.partial_cmp(b).expect("hallway coordinate is NaN"));
+    values.dedup_by(|a, b| (*a - *b).abs() <= T::TOLERANCE);
+    values
+}
+
+fn index<T: Scalar>(coordinates: &[T], value: T) -> usize {
+    coordinates.iter().position(|&coordinate| (coordinate - value).abs() <= T::TOLERANCE).expect("hallway coordinate not on grid")
+}
+
+/// Follows boundary edges from `start` until the loop closes, turning left whenever two
+/// hallways only touch at a corner so each loop stays simple.
+fn walk(edges: &mut BTreeMap<Vertex, Vec<Vertex>>, start: Vertex) -> Vec<Vertex> {
+    let mut outline = vec![start];
+    let mut current = start;
+    let mut direction : Option<(i64, i64)> = None;
+    loop {
+        let next = {
+            let outgoing = edges.get_mut(&current).expect("hallway outline is not closed");
+            let choice = match direction {
+                Some(incoming) if outgoing.len() > 1 => {
+                    let left = (-incoming.1, incoming.0);
+                    outgoing.iter().position(|&next| step(current, next) == left).unwrap_or(0)
+                },
+                _ => 0
+            };
+            outgoing.swap_remove(choice)
+        };
+        if edges[&current].is_empty() {
+            edges.remove(&current);
+        }
+        let next_direction = step(current, next);
+        if direction == Some(next_direction) {
+            outline.pop();
+        }
+        direction = Some(next_direction);
+        if next == start {
+            if step(outline[outline.len() - 1], start) == step(start, outline[1]) {
+                outline.remove(0);
+            }
+            return outline;
+        }
+        outline.push(next);
+        current = next;
+    }
+}
+
+fn step(from: Vertex, to: Vertex) -> (i64, i64) {
+    ((to.0 as i64 - from.0 as i64).signum(), (to.1 as i64 - from.1 as i64).signum())
+}
+
+/// Twice the signed area, positive for loops walked counter-clockwise.
+fn signed_area<T: Scalar>(points: &[Point<T>]) -> T {
+    segment::polygon_edges(points).iter()
+        .fold(T::ZERO, |sum, &(start, end)| sum + start.x() * end.y() - end.x() * start.y())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use chunk::{Chunk, ChunkSplit};
+
+    fn chunk(lower_left: (f32, f32), upper_right: (f32, f32)) -> Chunk {
+        Chunk::new(Point::new(lower_left.0, lower_left.1), Point::new(upper_right.0, upper_right.1), ChunkSplit::Horizontal)
+    }
+
+    #[test]
+    fn test_merge_t_junction() {
+        let mut hallway_builder = HallwayBuilder::new();
+        hallway_builder.add_chunk(chunk((0f32, 0f32), (10f32, 2f32)));
+        hallway_builder.add_chunk(chunk((4f32, 2f32), (6f32, 10f32)));
+        let hallways = hallway_builder.merge_hallways();
+        assert_eq!(1, hallways.len());
+        assert_eq!(8, hallways[0].points().len());
+        assert!(hallways[0].holes().is_empty());
+        assert!(hallways[0].contains(&Point::new(5f32, 8f32)));
+        assert!(!hallways[0].contains(&Point::new(2f32, 8f32)));
+    }
+
+    #[test]
+    fn test_merge_ring() {
+        let mut hallway_builder = HallwayBuilder::new();
+        hallway_builder.add_chunk(chunk((0f32, 0f32), (10f32, 2f32)));
+        hallway_builder.add_chunk(chunk((0f32, 8f32), (10f32, 10f32)));
+        hallway_builder.add_chunk(chunk((0f32, 2f32), (2f32, 8f32)));
+        hallway_builder.add_chunk(chunk((8f32, 2f32), (10f32, 8f32)));
+        let hallways = hallway_builder.merge_hallways();
+        assert_eq!(1, hallways.len());
+        assert_eq!(4, hallways[0].points().len());
+        assert_eq!(1, hallways[0].holes().len());
+        assert_eq!(4, hallways[0].holes()[0].len());
+        assert!(hallways[0].contains(&Point::new(1f32, 5f32)));
+        assert!(!hallways[0].contains(&Point::new(5f32, 5f32)));
+    }
+
+    #[test]
+    fn test_merge_separate() {
+        let mut hallway_builder = HallwayBuilder::new();
+        hallway_builder.add_chunk(chunk((0f32, 0f32), (10f32, 2f32)));
+        hallway_builder.add_chunk(chunk((10f32, 2f32), (12f32, 10f32)));
+        hallway_builder.add_chunk(chunk((20f32, 0f32), (22f32, 10f32)));
+        let hallways = hallway_builder.merge_hallways();
+        assert_eq!(3, hallways.len());
+        assert!(hallways.iter().all(|hallway| hallway.points().len() == 4));
+    }
+
+    #[test]
+    fn test_merge_nothing() {
+        assert!(HallwayBuilder::<f32>::new().merge_hallways().is_empty());
     }
 }
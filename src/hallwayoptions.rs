@@ -1,17 +1,107 @@
-pub struct HallwayOptions {
+use scalar::Scalar;
+use rand::Rng;
+
+/// How the `hallway_percent` budget is handed out while splitting.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HallwayBudget {
+    /// Any split may strip a hallway until the dungeon as a whole reaches the percentage,
+    /// so the first splits tend to take it all.
+    Global,
+    /// Every chunk gets a share of the budget by area, which its pieces divide again
+    /// after each split. A split only strips a hallway its own share pays for.
+    Spread,
+}
+
+/// How wide the hallway stripped along a split is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WidthScaling {
+    /// Anywhere between the minimum and maximum width.
+    Uniform,
+    /// The maximum width along the first split, narrowing with every level of the tree
+    /// down to the minimum at the given depth.
+    Depth(usize),
+    /// The maximum width along a split through the largest area, narrowing with the
+    /// length of the split line.
+    Size,
+}
+
+/// Where the hallway goes relative to the line a chunk was split along.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HallwayPlacement {
+    /// Entirely in the lower or left piece.
+    Lower,
+    /// Entirely in the upper or right piece.
+    Upper,
+    /// Straddling the split line.
+    Centered,
+    /// In one of the two pieces, picked at random for every split.
+    EitherSide,
+}
+
+pub struct HallwayOptions<T = f32> {
     pub hallway_percent: f32,
-    pub min_hallway_length: f32,
-    pub min_hallway_width: f32,
-    pub max_hallway_width: f32,
+    pub budget: HallwayBudget,
+    pub min_hallway_length: T,
+    /// Longest straight hallway a single split may strip. Longer splits get one this long
+    /// along the start of the line, and the rooms on both sides meet along the rest.
+    pub max_hallway_length: Option<T>,
+    pub min_hallway_width: T,
+    pub max_hallway_width: T,
+    pub width_scaling: WidthScaling,
+    pub placement: HallwayPlacement,
+    pub door_width: T,
 }
 
-impl HallwayOptions {
-    pub fn new(hallway_percent: f32, min_hallway_length: f32, min_hallway_width: f32, max_hallway_width: f32) -> HallwayOptions {
+impl<T: Scalar> HallwayOptions<T> {
+    pub fn new(hallway_percent: f32, min_hallway_length: T, min_hallway_width: T, max_hallway_width: T) -> HallwayOptions<T> {
         HallwayOptions { 
-            hallway_percent: hallway_percent, 
-            min_hallway_length: min_hallway_length, 
-            min_hallway_width: min_hallway_width, 
-            max_hallway_width: max_hallway_width
+            hallway_percent, 
+            budget: HallwayBudget::Global,
+            min_hallway_length, 
+            max_hallway_length: None,
+            min_hallway_width, 
+            max_hallway_width,
+            width_scaling: WidthScaling::Uniform,
+            placement: HallwayPlacement::Lower,
+            door_width: min_hallway_width
         }
     }
+
+    /// Width of a hallway along a split line of `length` at `depth` in the tree, where
+    /// `longest` is the longest side of any area being split.
+    pub fn hallway_width<R: Rng>(&self, depth: usize, length: T, longest: T, rng: &mut R) -> T {
+        let scale = match self.width_scaling {
+            WidthScaling::Uniform => return rng.gen_range(self.min_hallway_width, self.max_hallway_width),
+            WidthScaling::Depth(levels) if depth >= levels => 0f64,
+            WidthScaling::Depth(levels) => 1f64 - depth as f64 / levels as f64,
+            WidthScaling::Size => (length.to_f64() / longest.to_f64()).min(1f64)
+        };
+        let range = (self.max_hallway_width - self.min_hallway_width).to_f64();
+        T::from_f64(self.min_hallway_width.to_f64() + range * scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, Isaac64Rng};
+
+    #[test]
+    fn test_depth_scaling() {
+        let mut rng = Isaac64Rng::from_seed(&[1]);
+        let mut hallway_options = HallwayOptions::new(15f32, 0f32, 2f32, 6f32);
+        hallway_options.width_scaling = WidthScaling::Depth(4);
+        let widths : Vec<f32> = (0..6).map(|depth| hallway_options.hallway_width(depth, 100f32, 200f32, &mut rng)).collect();
+        assert_eq!(vec![6f32, 5f32, 4f32, 3f32, 2f32, 2f32], widths);
+    }
+
+    #[test]
+    fn test_size_scaling() {
+        let mut rng = Isaac64Rng::from_seed(&[1]);
+        let mut hallway_options = HallwayOptions::new(15f32, 0i32, 2i32, 6i32);
+        hallway_options.width_scaling = WidthScaling::Size;
+        assert_eq!(6, hallway_options.hallway_width(0, 200, 200, &mut rng));
+        assert_eq!(4, hallway_options.hallway_width(3, 100, 200, &mut rng));
+        assert_eq!(2, hallway_options.hallway_width(1, 10, 200, &mut rng));
+    }
 }
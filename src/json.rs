@@ -0,0 +1,273 @@
+/// A parsed JSON document. Objects keep their keys in the order they were written.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match *self {
+            Json::Object(ref fields) => fields.iter().find(|field| field.0 == key).map(|field| &field.1),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match *self {
+            Json::Array(ref items) => Some(&items[..]),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Json::Number(number) => Some(number),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Json::Text(ref text) => Some(text),
+            _ => None
+        }
+    }
+}
+
+/// Deepest nesting of objects and arrays `parse` accepts before giving up, well short of
+/// running out of stack.
+const MAX_DEPTH: usize = 128;
+
+/// Reads a JSON document, or returns the byte offset where it stops making sense.
+pub fn parse(text: &str) -> Result<Json, usize> {
+    let mut reader = Reader { bytes: text.as_bytes(), at: 0, depth: 0 };
+    let value = reader.value()?;
+    reader.skip_space();
+    if reader.at < reader.bytes.len() {
+        return Err(reader.at);
+    }
+    Ok(value)
+}
+
+/// Writes `text` as a quoted JSON string.
+pub fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c)
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+    depth: usize
+}
+
+impl<'a> Reader<'a> {
+    fn skip_space(&mut self) {
+        while self.at < self.bytes.len() && (self.bytes[self.at] as char).is_ascii_whitespace() {
+            self.at += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_space();
+        self.bytes.get(self.at).cloned()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), usize> {
+        if self.peek() == Some(byte) {
+            self.at += 1;
+            Ok(())
+        } else {
+            Err(self.at)
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, usize> {
+        if self.bytes[self.at..].starts_with(word.as_bytes()) {
+            self.at += word.len();
+            Ok(value)
+        } else {
+            Err(self.at)
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, usize> {
+        match self.peek() {
+            Some(b'{') => self.nested(Reader::object),
+            Some(b'[') => self.nested(Reader::array),
+            Some(b'"') => self.string().map(Json::Text),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            _ => Err(self.at)
+        }
+    }
+
+    fn nested(&mut self, read: fn(&mut Reader<'a>) -> Result<Json, usize>) -> Result<Json, usize> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.at);
+        }
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<Json, usize> {
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        if self.peek() == Some(b'}') {
+            self.at += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            if self.peek() != Some(b'"') {
+                return Err(self.at);
+            }
+            let key = self.string()?;
+            self.expect(b':')?;
+            fields.push((key, self.value()?));
+            match self.peek() {
+                Some(b',') => self.at += 1,
+                Some(b'}') => {
+                    self.at += 1;
+                    return Ok(Json::Object(fields));
+                },
+                _ => return Err(self.at)
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, usize> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        if self.peek() == Some(b']') {
+            self.at += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            match self.peek() {
+                Some(b',') => self.at += 1,
+                Some(b']') => {
+                    self.at += 1;
+                    return Ok(Json::Array(items));
+                },
+                _ => return Err(self.at)
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, usize> {
+        let start = self.at;
+        while self.at < self.bytes.len() && matches!(self.bytes[self.at], b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') {
+            self.at += 1;
+        }
+        ::std::str::from_utf8(&self.bytes[start..self.at]).ok()
+            .and_then(|number| number.parse::<f64>().ok())
+            .map(Json::Number)
+            .ok_or(start)
+    }
+
+    fn string(&mut self) -> Result<String, usize> {
+        self.expect(b'"')?;
+        let mut text = String::new();
+        loop {
+            let start = self.at;
+            while self.at < self.bytes.len() && self.bytes[self.at] != b'"' && self.bytes[self.at] != b'\\' {
+                self.at += 1;
+            }
+            text.push_str(::std::str::from_utf8(&self.bytes[start..self.at]).map_err(|_| start)?);
+            match self.bytes.get(self.at) {
+                Some(b'"') => {
+                    self.at += 1;
+                    return Ok(text);
+                },
+                Some(b'\\') => {
+                    let escaped = match self.bytes.get(self.at + 1) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.at += 2;
+                            let mut code = self.hex()?;
+                            if (0xd800..0xdc00).contains(&code) && self.bytes[self.at..].starts_with(b"\\u") {
+                                self.at += 2;
+                                code = 0x10000 + ((code - 0xd800) << 10) + (self.hex()?.wrapping_sub(0xdc00) & 0x3ff);
+                            }
+                            text.push(::std::char::from_u32(code).ok_or(self.at)?);
+                            continue;
+                        },
+                        _ => return Err(self.at)
+                    };
+                    text.push(escaped);
+                    self.at += 2;
+                },
+                _ => return Err(self.at)
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, usize> {
+        let digits = self.bytes.get(self.at..self.at + 4).ok_or(self.at)?;
+        let code = ::std::str::from_utf8(digits).ok().and_then(|digits| u32::from_str_radix(digits, 16).ok()).ok_or(self.at)?;
+        self.at += 4;
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let json = parse(" {\"a\": [1, -2.5e1, true, null], \"b\": {\"c\": \"x\\\"\\u00e9\\ud83d\\ude00\"}, \"d\": []} ").unwrap();
+        assert_eq!(Some(&[Json::Number(1f64), Json::Number(-25f64), Json::Bool(true), Json::Null][..]), json.get("a").and_then(|a| a.as_array()));
+        assert_eq!(Some("x\"é😀"), json.get("b").and_then(|b| b.get("c")).and_then(|c| c.as_str()));
+        assert_eq!(Some(0), json.get("d").and_then(|d| d.as_array()).map(|d| d.len()));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(Err(MAX_DEPTH), parse(&nested(MAX_DEPTH + 1)));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Err(6), parse("{\"a\":1"));
+        assert_eq!(Err(4), parse("[1, ]"));
+        assert_eq!(Err(3), parse("[] x"));
+        assert_eq!(Err(5), parse("\"open"));
+    }
+
+    #[test]
+    fn test_quote_round_trip() {
+        let text = "line\n\t\"quoted\" \\ é";
+        assert_eq!(Ok(Json::Text(text.to_string())), parse(&quote(text)));
+    }
+}
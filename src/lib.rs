@@ -3,10 +3,51 @@ extern crate rand;
 pub mod dungeonbuilder;
 mod hallwaybuilder;
 pub mod dungeon;
+pub mod scalar;
 pub mod point;
+pub mod rect;
+pub mod adjacency;
 pub mod hallway;
 pub mod room;
 pub mod dimensionoptions;
+pub mod splitstrategy;
+pub mod splitorder;
+pub mod sizedistribution;
 pub mod hallwayoptions;
+pub mod door;
+pub mod loopoptions;
+pub mod connectivityoptions;
+pub mod corridoroptions;
+pub mod roomrole;
+pub mod roleoptions;
+pub mod dungeonanalysis;
+pub mod spawnpoint;
+pub mod spawnoptions;
+pub mod wall;
+pub mod walloptions;
+pub mod mesh;
+pub mod meshoptions;
+pub mod tilemap;
+pub mod tiledoptions;
+pub mod dungeonjson;
+pub mod navmesh;
+pub mod spatialindex;
+pub mod prefab;
+pub mod worldoptions;
+pub mod sector;
+pub mod world;
 
 mod chunk;
+mod corridorbuilder;
+mod segment;
+mod roomgraph;
+mod doorbuilder;
+mod loopbuilder;
+mod connectivitybuilder;
+mod roleassigner;
+mod spawnbuilder;
+mod wallbuilder;
+mod meshbuilder;
+mod triangulate;
+mod tilemapbuilder;
+mod json;
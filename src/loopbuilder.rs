@@ -0,0 +1,110 @@
+use dungeon::Dungeon;
+use door::{Door, DoorKind, Region};
+use room::RoomId;
+use loopoptions::LoopOptions;
+use roomgraph::{self, RoomGraph};
+use scalar::Scalar;
+use segment;
+use rand::Rng;
+use std::collections::HashMap;
+
+pub struct LoopBuilder<'a, T: 'a> {
+    loop_options: &'a LoopOptions<T>
+}
+
+impl<'a, T: Scalar> LoopBuilder<'a, T> {
+    pub fn new(loop_options: &'a LoopOptions<T>) -> LoopBuilder<'a, T> {
+        LoopBuilder { loop_options }
+    }
+
+    /// Adds doors between rooms that share a wall but are far apart in the room graph,
+    /// always picking the pair with the longest detour first.
+    pub fn add_loops<R: Rng>(&self, dungeon: &mut Dungeon<T>, rng: &mut R) -> usize {
+        let mut graph = RoomGraph::new(dungeon.rooms().len(), dungeon.hallways().len(), dungeon.doors());
+        let mut candidates = roomgraph::shared_walls(dungeon.rooms());
+        candidates.retain(|&(a, b, _)| !dungeon.doors().iter().any(|door| door.connects(Region::Room(a), Region::Room(b))));
+        rng.shuffle(&mut candidates);
+        let mut added = 0usize;
+        while added < self.loop_options.max_loops {
+            let mut distances : HashMap<RoomId, Vec<Option<usize>>> = HashMap::new();
+            let mut best : Option<(usize, usize)> = None;
+            for (idx, &(a, b, _)) in candidates.iter().enumerate() {
+                let from_a = distances.entry(a).or_insert_with(|| graph.distances(Region::Room(a)));
+                let distance = match from_a[graph.node(Region::Room(b))] {
+                    Some(distance) => distance,
+                    None => continue
+                };
+                let is_better = match best {
+                    Some((_, best_distance)) => distance > best_distance,
+                    None => true
+                };
+                if distance >= self.loop_options.min_graph_distance && is_better {
+                    best = Some((idx, distance));
+                }
+            }
+            match best {
+                Some((idx, _)) => {
+                    let (a, b, wall) = candidates.swap_remove(idx);
+                    let opening = segment::centered(&wall, self.loop_options.door_width);
+                    dungeon.add_door(Door::new(Region::Room(a), Region::Room(b), opening.0, opening.1, DoorKind::Loop));
+                    graph.add_edge(Region::Room(a), Region::Room(b));
+                    added += 1;
+                },
+                None => break
+            }
+        }
+        added
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use room::Room;
+    use dungeon::Dungeon;
+    use door::{Door, DoorKind, Region};
+    use loopoptions::LoopOptions;
+    use chunk::{Chunk, ChunkSplit};
+    use rand::thread_rng;
+
+    // Four rooms in a square where room 0 and room 1 share a wall but are only
+    // connected by walking around through rooms 2 and 3.
+    fn ring_dungeon() -> Dungeon {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0f32, 0f32), Point::new(10f32, 10f32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(10f32, 0f32), Point::new(20f32, 10f32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0f32, 10f32), Point::new(10f32, 20f32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(10f32, 10f32), Point::new(20f32, 20f32), ChunkSplit::Vertical)));
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(0)), Region::Room(RoomId::new(2)), Point::new(4f32, 10f32), Point::new(6f32, 10f32), DoorKind::Loop));
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(2)), Region::Room(RoomId::new(3)), Point::new(10f32, 14f32), Point::new(10f32, 16f32), DoorKind::Loop));
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(3)), Region::Room(RoomId::new(1)), Point::new(14f32, 10f32), Point::new(16f32, 10f32), DoorKind::Loop));
+        dungeon
+    }
+
+    #[test]
+    fn test_add_loop() {
+        let mut dungeon = ring_dungeon();
+        let added = LoopBuilder::new(&LoopOptions::new(5, 3, 2f32)).add_loops(&mut dungeon, &mut thread_rng());
+        assert_eq!(1, added);
+        let door = dungeon.doors().last().unwrap();
+        assert!(door.connects(Region::Room(RoomId::new(0)), Region::Room(RoomId::new(1))));
+        assert!(*door.start() == Point::new(10f32, 4f32));
+        assert!(*door.end() == Point::new(10f32, 6f32));
+    }
+
+    #[test]
+    fn test_no_loop_below_distance() {
+        let mut dungeon = ring_dungeon();
+        let added = LoopBuilder::new(&LoopOptions::new(5, 4, 2f32)).add_loops(&mut dungeon, &mut thread_rng());
+        assert_eq!(0, added);
+        assert_eq!(3, dungeon.doors().len());
+    }
+
+    #[test]
+    fn test_max_loops() {
+        let mut dungeon = ring_dungeon();
+        let added = LoopBuilder::new(&LoopOptions::new(0, 1, 2f32)).add_loops(&mut dungeon, &mut thread_rng());
+        assert_eq!(0, added);
+    }
+}
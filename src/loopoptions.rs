@@ -0,0 +1,17 @@
+use scalar::Scalar;
+
+pub struct LoopOptions<T = f32> {
+    pub max_loops: usize,
+    pub min_graph_distance: usize,
+    pub door_width: T,
+}
+
+impl<T: Scalar> LoopOptions<T> {
+    pub fn new(max_loops: usize, min_graph_distance: usize, door_width: T) -> LoopOptions<T> {
+        LoopOptions { 
+            max_loops, 
+            min_graph_distance, 
+            door_width 
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt::Write;
+
+/// What a group of triangles is made of. Exporters turn each into a named material.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MeshMaterial {
+    Room,
+    Hallway,
+    Wall,
+}
+
+impl MeshMaterial {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            MeshMaterial::Room => "room",
+            MeshMaterial::Hallway => "hallway",
+            MeshMaterial::Wall => "wall"
+        }
+    }
+
+    fn color(&self) -> [f32; 3] {
+        match *self {
+            MeshMaterial::Room => [0.6f32, 0.5f32, 0.4f32],
+            MeshMaterial::Hallway => [0.4f32, 0.4f32, 0.45f32],
+            MeshMaterial::Wall => [0.8f32, 0.8f32, 0.75f32]
+        }
+    }
+}
+
+/// The triangles sharing one material, as indices into the mesh vertices.
+pub struct MeshGroup {
+    material: MeshMaterial,
+    triangles: Vec<[usize; 3]>
+}
+
+impl MeshGroup {
+    pub fn material(&self) -> MeshMaterial {
+        self.material
+    }
+
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles[..]
+    }
+}
+
+/// A triangle mesh in a y-up space, where the dungeon's x axis stays x and its y axis
+/// runs along negative z. Triangles are counterclockwise seen from outside.
+pub struct Mesh {
+    vertices: Vec<[f64; 3]>,
+    groups: Vec<MeshGroup>
+}
+
+impl Mesh {
+    pub fn new() -> Mesh {
+        Mesh { vertices: Vec::new(), groups: Vec::new() }
+    }
+
+    /// Adds a vertex and returns its index.
+    pub fn add_vertex(&mut self, vertex: [f64; 3]) -> usize {
+        self.vertices.push(vertex);
+        self.vertices.len() - 1
+    }
+
+    pub fn add_triangle(&mut self, material: MeshMaterial, triangle: [usize; 3]) {
+        match self.groups.iter().position(|group| group.material == material) {
+            Some(idx) => self.groups[idx].triangles.push(triangle),
+            None => self.groups.push(MeshGroup { material, triangles: vec![triangle] })
+        }
+    }
+
+    pub fn vertices(&self) -> &[[f64; 3]] {
+        &self.vertices[..]
+    }
+
+    pub fn groups(&self) -> &[MeshGroup] {
+        &self.groups[..]
+    }
+
+    /// Writes the mesh as Wavefront OBJ with a group per material. Pass the name of a
+    /// file holding the output of `to_mtl` as `mtllib` to give the materials colors.
+    pub fn to_obj(&self, mtllib: Option<&str>) -> String {
+        let mut obj = String::new();
+        if let Some(mtllib) = mtllib {
+            writeln!(obj, "mtllib {}", mtllib).unwrap();
+        }
+        for vertex in &self.vertices {
+            writeln!(obj, "v {} {} {}", vertex[0], vertex[1], vertex[2]).unwrap();
+        }
+        for group in &self.groups {
+            writeln!(obj, "g {}\nusemtl {}", group.material.name(), group.material.name()).unwrap();
+            for triangle in &group.triangles {
+                writeln!(obj, "f {} {} {}", triangle[0] + 1, triangle[1] + 1, triangle[2] + 1).unwrap();
+            }
+        }
+        obj
+    }
+
+    /// The material library `to_obj` can refer to.
+    pub fn to_mtl(&self) -> String {
+        let mut mtl = String::new();
+        for group in &self.groups {
+            let color = group.material.color();
+            writeln!(mtl, "newmtl {}\nKd {} {} {}", group.material.name(), color[0], color[1], color[2]).unwrap();
+        }
+        mtl
+    }
+
+    /// Writes the mesh as a self-contained glTF 2.0 file, with the vertex and index data
+    /// embedded in the JSON and a primitive per material.
+    pub fn to_gltf(&self) -> String {
+        let mut gltf = String::from("{\"asset\":{\"version\":\"2.0\",\"generator\":\"dungeonbuilder\"}");
+        if self.groups.is_empty() {
+            gltf.push('}');
+            return gltf;
+        }
+        let mut buffer : Vec<u8> = Vec::new();
+        let (mut min, mut max) = ([f32::MAX; 3], [f32::MIN; 3]);
+        for vertex in &self.vertices {
+            for axis in 0..3 {
+                let value = vertex[axis] as f32;
+                min[axis] = min[axis].min(value);
+                max[axis] = max[axis].max(value);
+                buffer.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        let mut accessors = format!("{{\"bufferView\":0,\"componentType\":5126,\"count\":{},\"type\":\"VEC3\",\"min\":[{},{},{}],\"max\":[{},{},{}]}}",
+            self.vertices.len(), min[0], min[1], min[2], max[0], max[1], max[2]);
+        let mut views = format!("{{\"buffer\":0,\"byteOffset\":0,\"byteLength\":{},\"target\":34962}}", buffer.len());
+        let (mut primitives, mut materials) = (Vec::new(), Vec::new());
+        for (idx, group) in self.groups.iter().enumerate() {
+            let offset = buffer.len();
+            for index in group.triangles.iter().flat_map(|triangle| triangle.iter()) {
+                buffer.extend_from_slice(&(*index as u32).to_le_bytes());
+            }
+            write!(accessors, ",{{\"bufferView\":{},\"componentType\":5125,\"count\":{},\"type\":\"SCALAR\"}}", idx + 1, group.triangles.len() * 3).unwrap();
+            write!(views, ",{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":34963}}", offset, buffer.len() - offset).unwrap();
+            primitives.push(format!("{{\"attributes\":{{\"POSITION\":0}},\"indices\":{},\"material\":{}}}", idx + 1, idx));
+            let color = group.material.color();
+            materials.push(format!("{{\"name\":\"{}\",\"pbrMetallicRoughness\":{{\"baseColorFactor\":[{},{},{},1],\"metallicFactor\":0,\"roughnessFactor\":1}}}}",
+                group.material.name(), color[0], color[1], color[2]));
+        }
+        write!(gltf, ",\"scene\":0,\"scenes\":[{{\"nodes\":[0]}}],\"nodes\":[{{\"mesh\":0,\"name\":\"dungeon\"}}]").unwrap();
+        write!(gltf, ",\"meshes\":[{{\"primitives\":[{}]}}],\"materials\":[{}]", primitives.join(","), materials.join(",")).unwrap();
+        write!(gltf, ",\"accessors\":[{}],\"bufferViews\":[{}]", accessors, views).unwrap();
+        write!(gltf, ",\"buffers\":[{{\"byteLength\":{},\"uri\":\"data:application/octet-stream;base64,{}\"}}]}}", buffer.len(), base64(&buffer)).unwrap();
+        gltf
+    }
+}
+
+impl Default for Mesh {
+    fn default() -> Mesh {
+        Mesh::new()
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * idx) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle() -> Mesh {
+        let mut mesh = Mesh::new();
+        let a = mesh.add_vertex([0f64, 0f64, 0f64]);
+        let b = mesh.add_vertex([1f64, 0f64, 0f64]);
+        let c = mesh.add_vertex([0f64, 0f64, -1f64]);
+        mesh.add_triangle(MeshMaterial::Room, [a, b, c]);
+        mesh
+    }
+
+    #[test]
+    fn test_obj() {
+        assert_eq!("mtllib dungeon.mtl\nv 0 0 0\nv 1 0 0\nv 0 0 -1\ng room\nusemtl room\nf 1 2 3\n", triangle().to_obj(Some("dungeon.mtl")));
+        assert_eq!("newmtl room\nKd 0.6 0.5 0.4\n", triangle().to_mtl());
+    }
+
+    #[test]
+    fn test_gltf() {
+        let gltf = triangle().to_gltf();
+        assert!(gltf.contains("\"count\":3,\"type\":\"VEC3\",\"min\":[0,0,-1],\"max\":[1,0,0]"));
+        assert!(gltf.contains("{\"buffer\":0,\"byteOffset\":36,\"byteLength\":12,\"target\":34963}"));
+        assert!(gltf.contains("{\"byteLength\":48,\"uri\":\"data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAAAAAAIC/AAAAAAEAAAACAAAA\"}"));
+        assert_eq!("{\"asset\":{\"version\":\"2.0\",\"generator\":\"dungeonbuilder\"}}", Mesh::new().to_gltf());
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+    }
+}
@@ -0,0 +1,115 @@
+use dungeon::Dungeon;
+use mesh::{Mesh, MeshMaterial};
+use meshoptions::MeshOptions;
+use triangulate;
+use point::Point;
+use scalar::Scalar;
+
+pub struct MeshBuilder<'a, T: 'a> {
+    mesh_options: &'a MeshOptions<T>
+}
+
+impl<'a, T: Scalar> MeshBuilder<'a, T> {
+    pub fn new(mesh_options: &'a MeshOptions<T>) -> MeshBuilder<'a, T> {
+        MeshBuilder { mesh_options }
+    }
+
+    /// Floors of the rooms and hallways, and the walls standing on them.
+    pub fn mesh(&self, dungeon: &Dungeon<T>) -> Mesh {
+        let mut mesh = Mesh::new();
+        let floor = -self.mesh_options.floor_thickness.to_f64();
+        let top = self.mesh_options.wall_height.to_f64();
+        for room in dungeon.rooms() {
+            extrude(&mut mesh, MeshMaterial::Room, &points(&room.rect().corners()), &[], floor, 0f64);
+        }
+        for hallway in dungeon.hallways() {
+            let holes : Vec<Vec<Point<f64>>> = hallway.holes().iter().map(|hole| points(hole)).collect();
+            extrude(&mut mesh, MeshMaterial::Hallway, &points(hallway.points()), &holes, floor, 0f64);
+        }
+        for wall in dungeon.walls() {
+            extrude(&mut mesh, MeshMaterial::Wall, &points(wall.points()), &[], 0f64, top);
+        }
+        mesh
+    }
+}
+
+fn points<T: Scalar>(points: &[Point<T>]) -> Vec<Point<f64>> {
+    points.iter().map(|point| point.cast::<f64>()).collect()
+}
+
+/// Adds a solid from `low` up to `high` with the outline and holes as its cross section,
+/// or just its top face when the two are level.
+fn extrude(mesh: &mut Mesh, material: MeshMaterial, outline: &[Point<f64>], holes: &[Vec<Point<f64>>], low: f64, high: f64) {
+    let triangles = triangulate::triangulate(outline, holes);
+    let section : Vec<Point<f64>> = outline.iter().chain(holes.iter().flat_map(|hole| hole.iter())).cloned().collect();
+    let layer = |mesh: &mut Mesh, height: f64| -> Vec<usize> {
+        section.iter().map(|point| mesh.add_vertex([point.x(), height, -point.y()])).collect()
+    };
+    let upper = layer(mesh, high);
+    for triangle in &triangles {
+        mesh.add_triangle(material, [upper[triangle[0]], upper[triangle[1]], upper[triangle[2]]]);
+    }
+    if high - low <= 0f64 {
+        return;
+    }
+    let lower = layer(mesh, low);
+    for triangle in &triangles {
+        mesh.add_triangle(material, [lower[triangle[2]], lower[triangle[1]], lower[triangle[0]]]);
+    }
+    let mut offset = 0;
+    for (ring, counterclockwise) in Some(outline).into_iter().map(|ring| (ring, true)).chain(holes.iter().map(|hole| (&hole[..], false))) {
+        let mut idxs : Vec<usize> = (offset..offset + ring.len()).collect();
+        offset += ring.len();
+        if (triangulate::signed_area(ring) > 0f64) != counterclockwise {
+            idxs.reverse();
+        }
+        for at in 0..idxs.len() {
+            let (a, b) = (idxs[at], idxs[(at + 1) % idxs.len()]);
+            mesh.add_triangle(material, [lower[a], lower[b], upper[b]]);
+            mesh.add_triangle(material, [lower[a], upper[b], upper[a]]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use room::Room;
+    use chunk::{Chunk, ChunkSplit};
+    use walloptions::WallOptions;
+
+    fn dungeon() -> Dungeon<i32> {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0i32, 0i32), Point::new(10i32, 10i32), ChunkSplit::Vertical)));
+        dungeon.build_walls(&WallOptions::new(2i32));
+        dungeon
+    }
+
+    fn group(mesh: &Mesh, material: MeshMaterial) -> &[[usize; 3]] {
+        mesh.groups().iter().find(|group| group.material() == material).unwrap().triangles()
+    }
+
+    #[test]
+    fn test_flat_floors() {
+        let mesh = MeshBuilder::new(&MeshOptions::new(3i32)).mesh(&dungeon());
+        assert_eq!(2, group(&mesh, MeshMaterial::Room).len());
+        assert_eq!(4 * 12, group(&mesh, MeshMaterial::Wall).len());
+        let heights : Vec<f64> = mesh.vertices().iter().map(|vertex| vertex[1]).collect();
+        assert!(heights.iter().all(|&height| height == 0f64 || height == 3f64));
+    }
+
+    #[test]
+    fn test_floor_slabs_face_outwards() {
+        let mut mesh_options = MeshOptions::new(3i32);
+        mesh_options.floor_thickness = 1;
+        let mesh = MeshBuilder::new(&mesh_options).mesh(&dungeon());
+        let floor = group(&mesh, MeshMaterial::Room);
+        assert_eq!(12, floor.len());
+        let vertices = mesh.vertices();
+        let volume : f64 = floor.iter().map(|triangle| {
+            let (a, b, c) = (vertices[triangle[0]], vertices[triangle[1]], vertices[triangle[2]]);
+            (a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0]) + a[2] * (b[0] * c[1] - b[1] * c[0])) / 6f64
+        }).sum();
+        assert!((volume - 100f64).abs() < 1e-9);
+    }
+}
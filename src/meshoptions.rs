@@ -0,0 +1,18 @@
+use scalar::Scalar;
+
+/// Heights `Dungeon::to_mesh` extrudes the dungeon to. Floors sit at zero, and walls
+/// stand on them up to `wall_height`.
+pub struct MeshOptions<T = f32> {
+    pub wall_height: T,
+    /// Depth of the slab under every floor. At zero floors are single faces.
+    pub floor_thickness: T,
+}
+
+impl<T: Scalar> MeshOptions<T> {
+    pub fn new(wall_height: T) -> MeshOptions<T> {
+        MeshOptions {
+            wall_height,
+            floor_thickness: T::ZERO
+        }
+    }
+}
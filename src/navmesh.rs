@@ -0,0 +1,405 @@
+use dungeon::Dungeon;
+use door::Region;
+use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+use segment::{self, Segment};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+pub struct Portal<T = f32> {
+    neighbour: usize,
+    start: Point<T>,
+    end: Point<T>
+}
+
+impl<T: Scalar> Portal<T> {
+    pub fn neighbour(&self) -> usize {
+        self.neighbour
+    }
+
+    pub fn start(&self) -> &Point<T> {
+        &self.start
+    }
+
+    pub fn end(&self) -> &Point<T> {
+        &self.end
+    }
+
+    pub fn midpoint(&self) -> Point<T> {
+        self.start.lerp(&self.end, 0.5f64)
+    }
+}
+
+/// A convex walkable area. Rooms map to a single polygon and hallways are cut into
+/// rectangles, so every polygon is an axis aligned rectangle listed clockwise from its
+/// lower left corner.
+pub struct NavPolygon<T = f32> {
+    rect: Rect<T>,
+    points: Vec<Point<T>>,
+    region: Region,
+    portals: Vec<Portal<T>>
+}
+
+impl<T: Scalar> NavPolygon<T> {
+    fn new(rect: Rect<T>, region: Region) -> NavPolygon<T> {
+        NavPolygon { rect, points: rect.corners().to_vec(), region, portals: Vec::new() }
+    }
+
+    pub fn points(&self) -> &[Point<T>] {
+        &self.points[..]
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    pub fn portals(&self) -> &[Portal<T>] {
+        &self.portals[..]
+    }
+
+    pub fn rect(&self) -> &Rect<T> {
+        &self.rect
+    }
+
+    pub fn lower_left(&self) -> &Point<T> {
+        self.rect.lower_left()
+    }
+
+    pub fn upper_right(&self) -> &Point<T> {
+        self.rect.upper_right()
+    }
+
+    pub fn center(&self) -> Point<T> {
+        self.rect.center()
+    }
+
+    pub fn contains(&self, point: &Point<T>) -> bool {
+        point.x() >= self.lower_left().x() - T::TOLERANCE && point.x() <= self.upper_right().x() + T::TOLERANCE &&
+            point.y() >= self.lower_left().y() - T::TOLERANCE && point.y() <= self.upper_right().y() + T::TOLERANCE
+    }
+
+    fn edges(&self) -> Vec<Segment<T>> {
+        self.rect.edges()
+    }
+}
+
+/// A route through the navigation mesh. `points` starts and ends at the query points and
+/// passes through the middle of every portal crossed on the way.
+pub struct NavPath<T = f32> {
+    polygons: Vec<usize>,
+    points: Vec<Point<T>>
+}
+
+impl<T: Scalar> NavPath<T> {
+    pub fn polygons(&self) -> &[usize] {
+        &self.polygons[..]
+    }
+
+    pub fn points(&self) -> &[Point<T>] {
+        &self.points[..]
+    }
+
+    pub fn length(&self) -> T {
+        self.points.windows(2).fold(T::ZERO, |sum, pair| sum + pair[0].distance(&pair[1]))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PathError {
+    OutsideDungeon,
+    NotConnected,
+}
+
+pub struct NavMesh<T = f32> {
+    polygons: Vec<NavPolygon<T>>
+}
+
+impl<T: Scalar> NavMesh<T> {
+    pub fn new(dungeon: &Dungeon<T>) -> NavMesh<T> {
+        let mut polygons = Vec::new();
+        for id in dungeon.room_ids() {
+            polygons.push(NavPolygon::new(*dungeon.room(id).rect(), Region::Room(id)));
+        }
+        let mut hallway_polygons = Vec::with_capacity(dungeon.hallways().len());
+        for (idx, hallway) in dungeon.hallways().iter().enumerate() {
+            let first = polygons.len();
+            for rect in hallway.rects() {
+                polygons.push(NavPolygon::new(rect, Region::Hallway(idx)));
+            }
+            hallway_polygons.push((first, polygons.len()));
+        }
+        let mut mesh = NavMesh { polygons };
+        for &(first, last) in &hallway_polygons {
+            for a in first..last {
+                for b in (a + 1)..last {
+                    if let Some(shared) = mesh.polygons[a].rect().shared_segment(mesh.polygons[b].rect()) {
+                        mesh.connect(a, b, shared);
+                    }
+                }
+            }
+        }
+        for door in dungeon.doors() {
+            let opening = (*door.start(), *door.end());
+            let from = mesh.door_polygon(door.from(), &opening, &hallway_polygons);
+            let to = mesh.door_polygon(door.to(), &opening, &hallway_polygons);
+            if let (Some((from, from_opening)), Some((to, to_opening))) = (from, to) {
+                let shared = segment::shared_segment(&from_opening, &to_opening).unwrap_or(from_opening);
+                mesh.connect(from, to, shared);
+            }
+        }
+        mesh
+    }
+
+    pub fn polygons(&self) -> &[NavPolygon<T>] {
+        &self.polygons[..]
+    }
+
+    pub fn polygon_at(&self, point: &Point<T>) -> Option<usize> {
+        (0..self.polygons.len()).find(|&idx| self.polygons[idx].contains(point))
+    }
+
+    /// A* search over the polygons, measuring distance between the portals crossed.
+    /// Costs are summed in `f64` so integer meshes do not round every step.
+    pub fn find_path(&self, from: &Point<T>, to: &Point<T>) -> Result<NavPath<T>, PathError> {
+        let start = self.polygon_at(from).ok_or(PathError::OutsideDungeon)?;
+        let goal = self.polygon_at(to).ok_or(PathError::OutsideDungeon)?;
+        let mut costs = vec![f64::MAX; self.polygons.len()];
+        let mut entries : Vec<Point<T>> = vec![*from; self.polygons.len()];
+        let mut previous : Vec<Option<(usize, usize)>> = vec![None; self.polygons.len()];
+        let mut open = BinaryHeap::new();
+        costs[start] = 0f64;
+        open.push(Candidate { polygon: start, estimate: distance(from, to) });
+        while let Some(Candidate { polygon, estimate }) = open.pop() {
+            if polygon == goal {
+                break;
+            }
+            if estimate > costs[polygon] + distance(&entries[polygon], to) + T::TOLERANCE.to_f64() {
+                continue;
+            }
+            for (portal_idx, portal) in self.polygons[polygon].portals.iter().enumerate() {
+                let midpoint = portal.midpoint();
+                let cost = costs[polygon] + distance(&entries[polygon], &midpoint);
+                if cost < costs[portal.neighbour] {
+                    costs[portal.neighbour] = cost;
+                    entries[portal.neighbour] = midpoint;
+                    previous[portal.neighbour] = Some((polygon, portal_idx));
+                    open.push(Candidate { polygon: portal.neighbour, estimate: cost + distance(&midpoint, to) });
+                }
+            }
+        }
+        if costs[goal] == f64::MAX {
+            return Err(PathError::NotConnected);
+        }
+        let mut polygons = vec![goal];
+        let mut points = vec![*to];
+        let mut current = goal;
+        while let Some((polygon, portal_idx)) = previous[current] {
+            points.push(self.polygons[polygon].portals[portal_idx].midpoint());
+            polygons.push(polygon);
+            current = polygon;
+        }
+        points.push(*from);
+        polygons.reverse();
+        points.reverse();
+        Ok(NavPath { polygons, points })
+    }
+
+    /// Pulls a path tight around the corners of the portals it crosses (the funnel
+    /// algorithm), giving the shortest polyline through the same polygons.
+    pub fn straighten(&self, path: &NavPath<T>) -> Vec<Point<T>> {
+        let from = path.points[0];
+        let to = path.points[path.points.len() - 1];
+        let mut portals = vec![(from, from)];
+        for pair in path.polygons.windows(2) {
+            let current = &self.polygons[pair[0]];
+            let portal = current.portals.iter().find(|portal| portal.neighbour == pair[1]).expect("path crosses a missing portal");
+            // The portal lies on the border of a rectangle, so the ray from its center
+            // through the portal midpoint always separates the two ends.
+            if cross(&current.center(), &portal.midpoint(), &portal.start) > 0f64 {
+                portals.push((portal.start, portal.end));
+            } else {
+                portals.push((portal.end, portal.start));
+            }
+        }
+        portals.push((to, to));
+
+        let mut points = vec![from];
+        let (mut apex, mut left, mut right) = (from, from, from);
+        let (mut left_idx, mut right_idx) = (0usize, 0usize);
+        let mut idx = 1usize;
+        while idx < portals.len() {
+            let (portal_left, portal_right) = portals[idx];
+            if cross(&apex, &right, &portal_right) >= 0f64 {
+                if apex == right || cross(&apex, &left, &portal_right) < 0f64 {
+                    right = portal_right;
+                    right_idx = idx;
+                } else {
+                    points.push(left);
+                    apex = left;
+                    right = apex;
+                    right_idx = left_idx;
+                    idx = left_idx + 1;
+                    continue;
+                }
+            }
+            if cross(&apex, &left, &portal_left) <= 0f64 {
+                if apex == left || cross(&apex, &right, &portal_left) > 0f64 {
+                    left = portal_left;
+                    left_idx = idx;
+                } else {
+                    points.push(right);
+                    apex = right;
+                    left = apex;
+                    left_idx = right_idx;
+                    idx = right_idx + 1;
+                    continue;
+                }
+            }
+            idx += 1;
+        }
+        if *points.last().expect("path has no points") != to {
+            points.push(to);
+        }
+        points
+    }
+
+    fn connect(&mut self, a: usize, b: usize, shared: Segment<T>) {
+        self.polygons[a].portals.push(Portal { neighbour: b, start: shared.0, end: shared.1 });
+        self.polygons[b].portals.push(Portal { neighbour: a, start: shared.0, end: shared.1 });
+    }
+
+    /// Finds the polygon of `region` whose outline holds the largest part of a door opening.
+    fn door_polygon(&self, region: Region, opening: &Segment<T>, hallway_polygons: &[(usize, usize)]) -> Option<(usize, Segment<T>)> {
+        let (first, last) = match region {
+            Region::Room(id) => (id.index(), id.index() + 1),
+            Region::Hallway(idx) => hallway_polygons[idx]
+        };
+        (first..last)
+            .filter_map(|idx| segment::longest_shared_segment(&self.polygons[idx].edges(), &[*opening]).map(|shared| (idx, shared)))
+            .max_by(|a, b| segment::length(&a.1).partial_cmp(&segment::length(&b.1)).unwrap_or(Ordering::Equal))
+    }
+}
+
+struct Candidate {
+    polygon: usize,
+    estimate: f64
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        other.estimate.partial_cmp(&self.estimate).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Eq for Candidate {
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Candidate) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+fn distance<T: Scalar>(a: &Point<T>, b: &Point<T>) -> f64 {
+    a.cast::<f64>().distance(&b.cast::<f64>())
+}
+
+/// Positive when `point` lies left of the line from `a` to `b`.
+fn cross<T: Scalar>(a: &Point<T>, b: &Point<T>, point: &Point<T>) -> f64 {
+    let (a, b, point) = (a.cast::<f64>(), b.cast::<f64>(), point.cast::<f64>());
+    (b.x() - a.x()) * (point.y() - a.y()) - (b.y() - a.y()) * (point.x() - a.x())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use dungeonbuilder::DungeonBuilder;
+    use dimensionoptions::DimensionOptions;
+    use hallwayoptions::HallwayOptions;
+    use connectivityoptions::ConnectivityOptions;
+    use chunk::{Chunk, ChunkSplit};
+    use door::{Door, DoorKind};
+    use hallway::Hallway;
+    use room::{Room, RoomId};
+    use scalar::Scalar;
+
+    fn room(lower_left: (f32, f32), upper_right: (f32, f32)) -> Room {
+        Room::new(Chunk::new(Point::new(lower_left.0, lower_left.1), Point::new(upper_right.0, upper_right.1), ChunkSplit::Vertical))
+    }
+
+    #[test]
+    fn test_generated_mesh() {
+        let dungeon = DungeonBuilder::new()
+            .in_area(Point::new(0f32,0f32), Point::new(300f32,300f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .with_hallway_options(HallwayOptions::new(15f32, 0f32, 3f32, 5f32))
+            .with_connectivity_options(ConnectivityOptions::new(2f32))
+            .build();
+        let mesh = NavMesh::new(&dungeon);
+        for (idx, polygon) in mesh.polygons().iter().enumerate() {
+            assert_eq!(4, polygon.points().len());
+            assert!(polygon.upper_right().x() > polygon.lower_left().x() && polygon.upper_right().y() > polygon.lower_left().y());
+            for portal in polygon.portals() {
+                assert!(mesh.polygons()[portal.neighbour()].portals().iter().any(|back| back.neighbour() == idx));
+                assert!(polygon.contains(&portal.midpoint()));
+            }
+        }
+        let rooms = dungeon.rooms();
+        let from = mesh.polygons()[0].center();
+        let to = mesh.polygons()[rooms.len() - 1].center();
+        let path = mesh.find_path(&from, &to).expect("connected dungeon has no path");
+        assert_eq!(path.points().len(), path.polygons().len() + 1);
+        assert!(path.points()[0] == from && *path.points().last().unwrap() == to);
+        for (idx, &polygon) in path.polygons().iter().enumerate() {
+            assert!(mesh.polygons()[polygon].contains(&path.points()[idx]));
+            assert!(mesh.polygons()[polygon].contains(&path.points()[idx + 1]));
+        }
+        assert!(path.length() >= from.distance(&to) - f32::TOLERANCE);
+        let straight = mesh.straighten(&path);
+        assert!(straight[0] == from && *straight.last().unwrap() == to);
+        let straight_length = straight.windows(2).fold(0f32, |sum, pair| sum + pair[0].distance(&pair[1]));
+        assert!(straight_length <= path.length() + f32::TOLERANCE);
+    }
+
+    #[test]
+    fn test_no_path_outside_mesh() {
+        let dungeon = DungeonBuilder::new()
+            .in_area(Point::new(0f32,0f32), Point::new(100f32,100f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .build();
+        let mesh = NavMesh::new(&dungeon);
+        assert_eq!(Some(PathError::OutsideDungeon), mesh.find_path(&Point::new(50f32, 50f32), &Point::new(500f32, 500f32)).err());
+    }
+
+    #[test]
+    fn test_dungeon_path_bends_around_corners() {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(room((0f32, 0f32), (10f32, 10f32)));
+        dungeon.add_room(room((20f32, 30f32), (40f32, 40f32)));
+        dungeon.add_room(room((50f32, 0f32), (60f32, 10f32)));
+        dungeon.add_hallways(vec![Hallway::new(vec![
+            Point::new(10f32, 0f32), Point::new(30f32, 0f32), Point::new(30f32, 30f32),
+            Point::new(26f32, 30f32), Point::new(26f32, 4f32), Point::new(10f32, 4f32)
+        ])]);
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(0)), Region::Hallway(0), Point::new(10f32, 0f32), Point::new(10f32, 4f32), DoorKind::Hallway));
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(1)), Region::Hallway(0), Point::new(26f32, 30f32), Point::new(30f32, 30f32), DoorKind::Hallway));
+
+        let path = dungeon.find_path(&Point::new(5f32, 8f32), &Point::new(35f32, 38f32)).expect("rooms are connected");
+        let expected = vec![
+            Point::new(5f32, 8f32), Point::new(10f32, 4f32), Point::new(26f32, 4f32),
+            Point::new(30f32, 30f32), Point::new(35f32, 38f32)
+        ];
+        assert!(path == expected, "unexpected path {:?}", path.iter().map(|point| point.to_string()).collect::<Vec<String>>());
+
+        assert_eq!(Some(PathError::NotConnected), dungeon.find_path(&Point::new(5f32, 8f32), &Point::new(55f32, 5f32)).err());
+        assert_eq!(Some(PathError::OutsideDungeon), dungeon.find_path(&Point::new(5f32, 8f32), &Point::new(15f32, 20f32)).err());
+    }
+}
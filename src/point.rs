@@ -1,48 +1,71 @@
+use scalar::Scalar;
 use std::cmp::Ordering;
 use std::fmt;
+// The operator traits stay behind `ops::` so that `point.add(..)` here resolves to
+// the inherent method rather than `Add::add`.
+use std::ops;
 
-#[derive(Copy, Clone, Default)]
-pub struct Point {
-    x: f32,
-    y: f32
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Point<T = f32> {
+    x: T,
+    y: T
 }
 
-impl Point {
-    pub fn new(x: f32, y: f32) -> Point {
-        Point { x: x, y: y }
+impl<T: Scalar> Point<T> {
+    pub fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
     }
 
-    pub fn x(&self) -> f32 {
+    pub fn x(&self) -> T {
         self.x
     }
 
-    pub fn set_x(&mut self, x: f32) {
+    pub fn set_x(&mut self, x: T) {
         self.x = x;
     }
 
-    pub fn set_y(&mut self, y: f32) {
+    pub fn set_y(&mut self, y: T) {
         self.y = y;
     }
 
-    pub fn y(&self) -> f32 {
+    pub fn y(&self) -> T {
         self.y
     }
 
-    pub fn divide(&mut self, divsor: f32) {
-        self.x = self.x / divsor;
-        self.y = self.y / divsor;
+    pub fn divide(&mut self, divsor: T) {
+        self.x /= divsor;
+        self.y /= divsor;
     }
 
-    pub fn add(&mut self, point: Point) {
-        self.x = self.x + point.x();
-        self.y = self.y + point.y();
+    /// Moves the point by `point`, the same as `+=`.
+    pub fn add(&mut self, point: Point<T>) {
+        *self += point;
+    }
+
+    pub fn distance(&self, other: &Point<T>) -> T {
+        let dx = (self.x - other.x).to_f64();
+        let dy = (self.y - other.y).to_f64();
+        T::from_f64((dx * dx + dy * dy).sqrt())
+    }
+
+    /// The point a fraction `t` of the way from `self` to `other`.
+    pub fn lerp(&self, other: &Point<T>, t: f64) -> Point<T> {
+        Point::new(
+            T::from_f64(self.x.to_f64() + (other.x - self.x).to_f64() * t),
+            T::from_f64(self.y.to_f64() + (other.y - self.y).to_f64() * t)
+        )
+    }
+
+    /// Converts the coordinates to another scalar type, rounding when it is an integer.
+    pub fn cast<U: Scalar>(&self) -> Point<U> {
+        Point::new(U::from_f64(self.x.to_f64()), U::from_f64(self.y.to_f64()))
     }
 
     pub fn hash(&self) -> u64 {
-        ((self.x * 10000f32) as u64) + (self.y as u64)
+        ((self.x.to_f64() * 10000f64) as u64) + (self.y.to_f64() as u64)
     }
 
-    pub fn compare_x_y(&self, other: &Point) -> Ordering {
+    pub fn compare_x_y(&self, other: &Point<T>) -> Ordering {
         if self.x.eq(&other.x()) && self.y.eq(&other.y()) {
             return Ordering::Equal;
         }
@@ -61,7 +84,7 @@ impl Point {
         Ordering::Greater
     }
 
-    pub fn compare_y_x(&self, other: &Point) -> Ordering {
+    pub fn compare_y_x(&self, other: &Point<T>) -> Ordering {
         if self.x.eq(&other.x()) && self.y.eq(&other.y()) {
             return Ordering::Equal;
         }
@@ -81,20 +104,47 @@ impl Point {
     }
 }
 
-impl Eq for Point {
+impl<T: PartialEq> Eq for Point<T> {
 }
 
-impl PartialEq for Point {
-    fn eq(&self, other: &Point) -> bool {
-        self.x.eq(&other.x()) && self.y.eq(&other.y())
+impl<T: PartialEq> PartialEq for Point<T> {
+    fn eq(&self, other: &Point<T>) -> bool {
+        self.x.eq(&other.x) && self.y.eq(&other.y)
+    }
+}
+
+impl<T: Scalar> ops::Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point::new(self.x + other.x, self.y + other.y)
     }
+}
+
+impl<T: Scalar> ops::AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Point<T>) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl<T: Scalar> ops::Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, other: Point<T>) -> Point<T> {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Scalar> ops::Mul<T> for Point<T> {
+    type Output = Point<T>;
 
-    fn ne(&self, other: &Point) -> bool {
-        !self.eq(other)
+    fn mul(self, factor: T) -> Point<T> {
+        Point::new(self.x * factor, self.y * factor)
     }
 }
 
-impl fmt::Display for Point {
+impl<T: Scalar> fmt::Display for Point<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "({}, {})", self.x, self.y)
     }
@@ -175,4 +225,47 @@ mod tests {
         assert_eq!(9f32, point.x());
         assert_eq!(17f32, point.y());
     }
+
+    #[test]
+    fn test_add_operators() {
+        let mut point = Point::new(10f32, 20f32) + Point::new(-1f32, -3f32);
+        assert_eq!(Point::new(9f32, 17f32), point);
+        point += Point::new(1f32, 1f32);
+        assert_eq!(Point::new(10f32, 18f32), point);
+    }
+
+    #[test]
+    fn test_sub_and_mul() {
+        let point = (Point::new(10f32, 20f32) - Point::new(4f32, 5f32)) * 2f32;
+        assert_eq!(Point::new(12f32, 30f32), point);
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(5f32, Point::new(1f32, 1f32).distance(&Point::new(4f32, 5f32)));
+    }
+
+    #[test]
+    fn test_lerp() {
+        let from = Point::new(0f32, 10f32);
+        let to = Point::new(10f32, 20f32);
+        assert_eq!(Point::new(2.5f32, 12.5f32), from.lerp(&to, 0.25));
+        assert_eq!(to, from.lerp(&to, 1.0));
+    }
+
+    #[test]
+    fn test_integer_point() {
+        let from = Point::new(0i32, 0i32);
+        let to = Point::new(3i32, 4i32);
+        assert_eq!(5, from.distance(&to));
+        assert_eq!(Point::new(2i32, 2i32), from.lerp(&to, 0.5));
+        assert_eq!(Point::new(6i32, 8i32), to * 2);
+    }
+
+    #[test]
+    fn test_cast() {
+        let point = Point::new(1.6f64, -2.2f64);
+        assert_eq!(Point::new(2i32, -2i32), point.cast::<i32>());
+        assert_eq!(Point::new(1.6f32, -2.2f32), point.cast::<f32>());
+    }
 }
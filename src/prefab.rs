@@ -0,0 +1,179 @@
+use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+use room::RoomId;
+
+/// How many times the area of its footprint a leaf may have and still be stamped with a
+/// prefab while it could be split further.
+const SNUG_FACTOR: f64 = 4f64;
+
+/// Counterclockwise quarter turns applied to a prefab before it is stamped.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rotation {
+    Identity,
+    Quarter,
+    Half,
+    ThreeQuarter,
+}
+
+impl Rotation {
+    pub fn degrees(&self) -> u32 {
+        match *self {
+            Rotation::Identity => 0,
+            Rotation::Quarter => 90,
+            Rotation::Half => 180,
+            Rotation::ThreeQuarter => 270,
+        }
+    }
+
+    fn is_sideways(&self) -> bool {
+        matches!(*self, Rotation::Quarter | Rotation::ThreeQuarter)
+    }
+}
+
+/// A hand made room. `width` and `height` give its footprint and `entries` its openings,
+/// relative to the lower left corner of the unrotated footprint. It can be stamped into
+/// any leaf at least as large as the footprint and no larger than `max_width` by
+/// `max_height`, with the limits turning along with the prefab.
+pub struct Prefab<T = f32> {
+    pub name: String,
+    pub width: T,
+    pub height: T,
+    pub max_width: Option<T>,
+    pub max_height: Option<T>,
+    pub entries: Vec<Point<T>>,
+    pub count: usize,
+}
+
+impl<T: Scalar> Prefab<T> {
+    pub fn new(name: &str, width: T, height: T, entries: Vec<Point<T>>) -> Prefab<T> {
+        Prefab {
+            name: name.to_string(),
+            width,
+            height,
+            max_width: None,
+            max_height: None,
+            entries,
+            count: 1
+        }
+    }
+
+    /// The rotations that let the prefab fill `rect` within its size limits.
+    pub fn rotations_in(&self, rect: &Rect<T>) -> Vec<Rotation> {
+        [Rotation::Identity, Rotation::Quarter, Rotation::Half, Rotation::ThreeQuarter].iter().cloned()
+            .filter(|rotation| {
+                let (width, height, max_width, max_height) = if rotation.is_sideways() {
+                    (self.height, self.width, self.max_height, self.max_width)
+                } else {
+                    (self.width, self.height, self.max_width, self.max_height)
+                };
+                rect.width() >= width && rect.height() >= height &&
+                    max_width.map_or(true, |max| rect.width() <= max) && max_height.map_or(true, |max| rect.height() <= max)
+            })
+            .collect()
+    }
+
+    /// Whether `rect` is small enough for the prefab to take it before it is split further.
+    pub fn is_snug(&self, rect: &Rect<T>) -> bool {
+        rect.area().to_f64() <= (self.width * self.height).to_f64() * SNUG_FACTOR
+    }
+
+    /// Where an unrotated point of the footprint ends up after `rotation`, still measured
+    /// from the lower left corner of the rotated footprint.
+    pub fn rotate(&self, point: &Point<T>, rotation: Rotation) -> Point<T> {
+        match rotation {
+            Rotation::Identity => *point,
+            Rotation::Quarter => Point::new(self.height - point.y(), point.x()),
+            Rotation::Half => Point::new(self.width - point.x(), self.height - point.y()),
+            Rotation::ThreeQuarter => Point::new(point.y(), self.width - point.x()),
+        }
+    }
+}
+
+/// A prefab stamped into a room. The rotated footprint sits in the middle of the room.
+pub struct PrefabPlacement<T = f32> {
+    prefab: usize,
+    name: String,
+    room: RoomId,
+    rect: Rect<T>,
+    rotation: Rotation,
+    entries: Vec<Point<T>>
+}
+
+impl<T: Scalar> PrefabPlacement<T> {
+    pub fn new(prefab_idx: usize, prefab: &Prefab<T>, room_id: RoomId, room: &Rect<T>, rotation: Rotation) -> PrefabPlacement<T> {
+        let size = if rotation.is_sideways() { Point::new(prefab.height, prefab.width) } else { Point::new(prefab.width, prefab.height) };
+        let margin = Point::new(room.width() - size.x(), room.height() - size.y());
+        let lower_left = *room.lower_left() + Point::new(margin.x() / (T::ONE + T::ONE), margin.y() / (T::ONE + T::ONE));
+        let entries = prefab.entries.iter().map(|entry| lower_left + prefab.rotate(entry, rotation)).collect();
+        PrefabPlacement {
+            prefab: prefab_idx,
+            name: prefab.name.clone(),
+            room: room_id,
+            rect: Rect::new(lower_left, lower_left + size),
+            rotation,
+            entries
+        }
+    }
+
+    /// Index of the prefab in the order it was registered with the builder.
+    pub fn prefab(&self) -> usize {
+        self.prefab
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn room(&self) -> RoomId {
+        self.room
+    }
+
+    /// The rotated footprint in dungeon coordinates.
+    pub fn rect(&self) -> &Rect<T> {
+        &self.rect
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// The prefab's entries in dungeon coordinates.
+    pub fn entries(&self) -> &[Point<T>] {
+        &self.entries[..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shop() -> Prefab {
+        let mut prefab = Prefab::new("shop", 20f32, 10f32, vec![Point::new(10f32, 0f32), Point::new(20f32, 5f32)]);
+        prefab.max_width = Some(30f32);
+        prefab.max_height = Some(15f32);
+        prefab
+    }
+
+    #[test]
+    fn test_rotations_in() {
+        let prefab = shop();
+        let wide = Rect::new(Point::new(0f32, 0f32), Point::new(25f32, 12f32));
+        assert_eq!(vec![Rotation::Identity, Rotation::Half], prefab.rotations_in(&wide));
+        let tall = Rect::new(Point::new(0f32, 0f32), Point::new(12f32, 25f32));
+        assert_eq!(vec![Rotation::Quarter, Rotation::ThreeQuarter], prefab.rotations_in(&tall));
+        assert!(prefab.rotations_in(&Rect::new(Point::new(0f32, 0f32), Point::new(40f32, 12f32))).is_empty());
+        assert!(prefab.rotations_in(&Rect::new(Point::new(0f32, 0f32), Point::new(15f32, 15f32))).is_empty());
+    }
+
+    #[test]
+    fn test_placement_rotates_entries() {
+        let prefab = shop();
+        let room = Rect::new(Point::new(100f32, 50f32), Point::new(112f32, 72f32));
+        let placement = PrefabPlacement::new(0, &prefab, RoomId::new(3), &room, Rotation::Quarter);
+        assert_eq!(Rect::new(Point::new(101f32, 51f32), Point::new(111f32, 71f32)), *placement.rect());
+        assert_eq!(&[Point::new(111f32, 61f32), Point::new(106f32, 71f32)], placement.entries());
+        assert_eq!(90, placement.rotation().degrees());
+        assert_eq!("shop", placement.name());
+    }
+}
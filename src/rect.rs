@@ -0,0 +1,222 @@
+use point::Point;
+use scalar::Scalar;
+use adjacency::{Adjacency, Orientation};
+use segment;
+
+/// An axis aligned rectangle, stored by its lower left and upper right corners.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct Rect<T = f32> {
+    lower_left: Point<T>,
+    upper_right: Point<T>
+}
+
+impl<T: PartialEq> Eq for Rect<T> {
+}
+
+impl<T: Scalar> Rect<T> {
+    /// Builds the rectangle spanned by two opposite corners, in either order.
+    pub fn new(a: Point<T>, b: Point<T>) -> Rect<T> {
+        Rect {
+            lower_left: Point::new(a.x().min(b.x()), a.y().min(b.y())),
+            upper_right: Point::new(a.x().max(b.x()), a.y().max(b.y()))
+        }
+    }
+
+    pub fn lower_left(&self) -> &Point<T> {
+        &self.lower_left
+    }
+
+    pub fn upper_right(&self) -> &Point<T> {
+        &self.upper_right
+    }
+
+    pub fn width(&self) -> T {
+        self.upper_right.x() - self.lower_left.x()
+    }
+
+    pub fn height(&self) -> T {
+        self.upper_right.y() - self.lower_left.y()
+    }
+
+    pub fn area(&self) -> T {
+        self.width() * self.height()
+    }
+
+    pub fn center(&self) -> Point<T> {
+        self.lower_left.lerp(&self.upper_right, 0.5)
+    }
+
+    /// The long side divided by the short one.
+    pub fn aspect_ratio(&self) -> f64 {
+        let (width, height) = (self.width().to_f64(), self.height().to_f64());
+        width.max(height) / width.min(height)
+    }
+
+    /// The corners listed clockwise from the lower left one.
+    pub fn corners(&self) -> [Point<T>; 4] {
+        [
+            self.lower_left,
+            Point::new(self.lower_left.x(), self.upper_right.y()),
+            self.upper_right,
+            Point::new(self.upper_right.x(), self.lower_left.y())
+        ]
+    }
+
+    pub fn edges(&self) -> Vec<(Point<T>, Point<T>)> {
+        segment::rect_edges(&self.lower_left, &self.upper_right)
+    }
+
+    /// True when `point` lies inside the rectangle or on its border.
+    pub fn contains(&self, point: &Point<T>) -> bool {
+        point.x() >= self.lower_left.x() && point.x() <= self.upper_right.x() &&
+            point.y() >= self.lower_left.y() && point.y() <= self.upper_right.y()
+    }
+
+    pub fn contains_rect(&self, other: &Rect<T>) -> bool {
+        self.contains(&other.lower_left) && self.contains(&other.upper_right)
+    }
+
+    /// True when the two rectangles share some area. Touching borders do not count.
+    pub fn intersects(&self, other: &Rect<T>) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    pub fn intersection(&self, other: &Rect<T>) -> Option<Rect<T>> {
+        let lower_left = Point::new(self.lower_left.x().max(other.lower_left.x()), self.lower_left.y().max(other.lower_left.y()));
+        let upper_right = Point::new(self.upper_right.x().min(other.upper_right.x()), self.upper_right.y().min(other.upper_right.y()));
+        if upper_right.x() - lower_left.x() > T::TOLERANCE && upper_right.y() - lower_left.y() > T::TOLERANCE {
+            Some(Rect { lower_left, upper_right })
+        } else {
+            None
+        }
+    }
+
+    /// The smallest rectangle covering both.
+    pub fn union(&self, other: &Rect<T>) -> Rect<T> {
+        Rect {
+            lower_left: Point::new(self.lower_left.x().min(other.lower_left.x()), self.lower_left.y().min(other.lower_left.y())),
+            upper_right: Point::new(self.upper_right.x().max(other.upper_right.x()), self.upper_right.y().max(other.upper_right.y()))
+        }
+    }
+
+    /// Distance from `point` to the closest point of the rectangle, zero inside it.
+    pub fn distance(&self, point: &Point<T>) -> T {
+        let dx = (self.lower_left.x() - point.x()).max(point.x() - self.upper_right.x()).max(T::ZERO).to_f64();
+        let dy = (self.lower_left.y() - point.y()).max(point.y() - self.upper_right.y()).max(T::ZERO).to_f64();
+        T::from_f64((dx * dx + dy * dy).sqrt())
+    }
+
+    /// The wall two rectangles have in common when they touch along an edge. Gaps and
+    /// overlaps up to `tolerance` still count as touching, while rectangles meeting only
+    /// at a corner or sharing area are not adjacent. Across a gap or overlap the wall
+    /// runs halfway between the two edges, so swapping the rectangles gives the same wall.
+    pub fn adjacency(&self, other: &Rect<T>, tolerance: T) -> Option<Adjacency<T>> {
+        let overlap_x = self.upper_right.x().min(other.upper_right.x()) - self.lower_left.x().max(other.lower_left.x());
+        let overlap_y = self.upper_right.y().min(other.upper_right.y()) - self.lower_left.y().max(other.lower_left.y());
+        let two = T::ONE + T::ONE;
+        if overlap_x.abs() <= tolerance && overlap_y > tolerance {
+            let x = if (self.upper_right.x() - other.lower_left.x()).abs() <= tolerance {
+                (self.upper_right.x() + other.lower_left.x()) / two
+            } else {
+                (self.lower_left.x() + other.upper_right.x()) / two
+            };
+            let low = self.lower_left.y().max(other.lower_left.y());
+            Some(Adjacency::new(Point::new(x, low), Point::new(x, low + overlap_y), Orientation::Vertical))
+        } else if overlap_y.abs() <= tolerance && overlap_x > tolerance {
+            let y = if (self.upper_right.y() - other.lower_left.y()).abs() <= tolerance {
+                (self.upper_right.y() + other.lower_left.y()) / two
+            } else {
+                (self.lower_left.y() + other.upper_right.y()) / two
+            };
+            let low = self.lower_left.x().max(other.lower_left.x());
+            Some(Adjacency::new(Point::new(low, y), Point::new(low + overlap_x, y), Orientation::Horizontal))
+        } else {
+            None
+        }
+    }
+
+    pub fn shared_segment(&self, other: &Rect<T>) -> Option<(Point<T>, Point<T>)> {
+        self.adjacency(other, T::TOLERANCE).map(|adjacency| adjacency.segment())
+    }
+
+    pub fn is_adjacent(&self, other: &Rect<T>) -> bool {
+        self.shared_segment(other).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use adjacency::Orientation;
+    use scalar::Scalar;
+
+    fn rect(lower_left: (f32, f32), upper_right: (f32, f32)) -> Rect {
+        Rect::new(Point::new(lower_left.0, lower_left.1), Point::new(upper_right.0, upper_right.1))
+    }
+
+    #[test]
+    fn test_new_orders_corners() {
+        let rect = Rect::new(Point::new(10f32, 0f32), Point::new(0f32, 5f32));
+        assert_eq!(Point::new(0f32, 0f32), *rect.lower_left());
+        assert_eq!(Point::new(10f32, 5f32), *rect.upper_right());
+        assert_eq!(50f32, rect.area());
+        assert_eq!(Point::new(5f32, 2.5f32), rect.center());
+    }
+
+    #[test]
+    fn test_intersection_and_union() {
+        let a = rect((0f32, 0f32), (10f32, 10f32));
+        let b = rect((5f32, 5f32), (20f32, 8f32));
+        assert_eq!(Some(rect((5f32, 5f32), (10f32, 8f32))), a.intersection(&b));
+        assert_eq!(rect((0f32, 0f32), (20f32, 10f32)), a.union(&b));
+        assert!(!a.intersects(&rect((10f32, 0f32), (20f32, 10f32))));
+    }
+
+    #[test]
+    fn test_contains() {
+        let a = rect((0f32, 0f32), (10f32, 10f32));
+        assert!(a.contains(&Point::new(10f32, 5f32)));
+        assert!(!a.contains(&Point::new(10.5f32, 5f32)));
+        assert!(a.contains_rect(&rect((2f32, 2f32), (10f32, 4f32))));
+        assert!(!a.contains_rect(&rect((2f32, 2f32), (11f32, 4f32))));
+        assert_eq!(5f32, a.distance(&Point::new(13f32, 14f32)));
+    }
+
+    #[test]
+    fn test_adjacency_tolerance() {
+        let a = rect((0f32, 0f32), (10f32, 10f32));
+        let b = rect((10.05f32, 2f32), (20f32, 8f32));
+        assert!(a.adjacency(&b, <f32 as Scalar>::TOLERANCE).is_none());
+        let adjacency = a.adjacency(&b, 0.1f32).unwrap();
+        assert_eq!(Orientation::Vertical, adjacency.orientation());
+        assert_eq!(6f32, adjacency.length());
+        assert_eq!(10.025f32, adjacency.start().x());
+        assert_eq!(Some(adjacency), b.adjacency(&a, 0.1f32));
+    }
+
+    #[test]
+    fn test_shared_segment() {
+        let a = rect((0f32, 0f32), (10f32, 10f32));
+        assert_eq!(Some((Point::new(10f32, 4f32), Point::new(10f32, 10f32))), a.shared_segment(&rect((10f32, 4f32), (20f32, 30f32))));
+        assert!(!a.is_adjacent(&rect((10f32, 10f32), (20f32, 20f32))));
+        assert!(!a.is_adjacent(&rect((5f32, 0f32), (20f32, 10f32))));
+    }
+
+    #[test]
+    fn test_integer_rect() {
+        let a = Rect::new(Point::new(0i32, 0i32), Point::new(5i32, 5i32));
+        let b = Rect::new(Point::new(5i32, 1i32), Point::new(9i32, 4i32));
+        assert_eq!(Point::new(3i32, 3i32), a.center());
+        assert!(!a.intersects(&b));
+        assert_eq!(3, a.adjacency(&b, 0).unwrap().length());
+        assert_eq!(5, a.distance(&Point::new(8i32, 9i32)));
+    }
+
+    #[test]
+    fn test_double_rect() {
+        let a = Rect::new(Point::new(1e7f64, 1e7f64), Point::new(1e7f64 + 0.5f64, 1e7f64 + 0.25f64));
+        assert_eq!(0.125f64, a.area());
+        assert!(a.contains(&Point::new(1e7f64 + 0.1f64, 1e7f64 + 0.1f64)));
+    }
+}
@@ -0,0 +1,147 @@
+use dungeon::Dungeon;
+use door::Region;
+use room::RoomId;
+use roleoptions::RoleOptions;
+use roomrole::RoomRole;
+use roomgraph::RoomGraph;
+use dungeonanalysis::DungeonAnalysis;
+use scalar::Scalar;
+use rand::Rng;
+
+pub struct RoleAssigner<'a, T: 'a> {
+    role_options: &'a RoleOptions<T>
+}
+
+impl<'a, T: Scalar> RoleAssigner<'a, T> {
+    pub fn new(role_options: &'a RoleOptions<T>) -> RoleAssigner<'a, T> {
+        RoleAssigner { role_options }
+    }
+
+    /// Returns the roles each room should receive. The entrance is chosen from the largest
+    /// connected component so that the farthest room is always reachable from it.
+    pub fn assign<R: Rng>(&self, dungeon: &Dungeon<T>, rng: &mut R) -> Vec<(RoomId, RoomRole)> {
+        let mut roles = Vec::new();
+        let graph = RoomGraph::new(dungeon.rooms().len(), dungeon.hallways().len(), dungeon.doors());
+        let largest = graph.components().into_iter()
+            .map(|component| component.into_iter().filter_map(|region| match region {
+                Region::Room(id) => Some(id),
+                Region::Hallway(_) => None
+            }).collect::<Vec<RoomId>>())
+            .max_by_key(|component| component.len())
+            .unwrap_or_default();
+        let mut entrances : Vec<RoomId> = largest.into_iter()
+            .filter(|&id| self.role_options.entrance.matches(dungeon.room(id).area(), None, graph.degree(Region::Room(id))))
+            .collect();
+        if entrances.is_empty() {
+            return roles;
+        }
+        rng.shuffle(&mut entrances);
+        let entrance = entrances[0];
+        roles.push((entrance, self.role_options.entrance.role.clone()));
+
+        let analysis = DungeonAnalysis::new(dungeon, entrance).expect("entrance is not a room");
+        if analysis.max_distance().unwrap_or(0) > 0 {
+            let mut farthest = analysis.farthest_rooms();
+            rng.shuffle(&mut farthest);
+            roles.push((farthest[0], self.role_options.farthest.clone()));
+        }
+
+        for rule in &self.role_options.rules {
+            let mut candidates : Vec<RoomId> = dungeon.room_ids()
+                .filter(|&id| !roles.iter().any(|&(room, ref role)| room == id && is_unique(role)))
+                .filter(|&id| rule.matches(dungeon.room(id).area(), analysis.room(id).distance(), analysis.room(id).degree()))
+                .collect();
+            rng.shuffle(&mut candidates);
+            if let Some(max_rooms) = rule.max_rooms {
+                candidates.truncate(max_rooms);
+            }
+            for id in candidates {
+                roles.push((id, rule.role.clone()));
+            }
+        }
+        roles
+    }
+}
+
+fn is_unique(role: &RoomRole) -> bool {
+    matches!(*role, RoomRole::Entrance | RoomRole::Exit | RoomRole::Boss)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use room::Room;
+    use dungeon::Dungeon;
+    use door::{Door, DoorKind, Region};
+    use roleoptions::{RoleOptions, RoleRule};
+    use roomrole::RoomRole;
+    use chunk::{Chunk, ChunkSplit};
+    use rand::thread_rng;
+
+    // A row of rooms where only the first one is large enough to be the entrance.
+    fn chain_dungeon() -> Dungeon {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0f32, 0f32), Point::new(20f32, 20f32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(20f32, 0f32), Point::new(30f32, 10f32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(30f32, 0f32), Point::new(40f32, 10f32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(40f32, 0f32), Point::new(50f32, 10f32), ChunkSplit::Vertical)));
+        for idx in 0..3 {
+            let x = dungeon.rooms()[idx].upper_right().x();
+            dungeon.add_door(Door::new(Region::Room(RoomId::new(idx)), Region::Room(RoomId::new(idx + 1)), Point::new(x, 4f32), Point::new(x, 6f32), DoorKind::Loop));
+        }
+        dungeon
+    }
+
+    #[test]
+    fn test_assign() {
+        let dungeon = chain_dungeon();
+        let mut role_options = RoleOptions::new();
+        role_options.entrance.min_area = Some(400f32);
+        let mut treasure = RoleRule::new(RoomRole::Treasure);
+        treasure.min_depth = Some(2);
+        treasure.max_degree = Some(2);
+        let role_options = role_options.with_rule(treasure);
+        let roles = RoleAssigner::new(&role_options).assign(&dungeon, &mut thread_rng());
+        assert!(roles.contains(&(RoomId::new(0), RoomRole::Entrance)));
+        assert!(roles.contains(&(RoomId::new(3), RoomRole::Boss)));
+        assert!(!roles.contains(&(RoomId::new(0), RoomRole::DeadEnd)));
+        assert!(!roles.contains(&(RoomId::new(3), RoomRole::DeadEnd)));
+        assert!(roles.contains(&(RoomId::new(2), RoomRole::Treasure)));
+        assert!(!roles.contains(&(RoomId::new(1), RoomRole::Treasure)));
+    }
+
+    #[test]
+    fn test_default_treasure() {
+        let mut dungeon = chain_dungeon();
+        let side = dungeon.add_room(Room::from_points(Point::new(20f32, -10f32), Point::new(30f32, 0f32)));
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(1)), Region::Room(side), Point::new(24f32, 0f32), Point::new(26f32, 0f32), DoorKind::Loop));
+        let mut role_options = RoleOptions::new();
+        role_options.entrance.min_area = Some(400f32);
+        let roles = RoleAssigner::new(&role_options).assign(&dungeon, &mut thread_rng());
+        assert!(roles.contains(&(RoomId::new(3), RoomRole::Boss)));
+        assert!(roles.contains(&(side, RoomRole::DeadEnd)));
+        assert_eq!(vec![side], roles.iter().filter(|&(_, role)| *role == RoomRole::Treasure).map(|&(room, _)| room).collect::<Vec<RoomId>>());
+    }
+
+    #[test]
+    fn test_isolated_room_is_no_dead_end() {
+        let mut dungeon = chain_dungeon();
+        let isolated = dungeon.add_room(Room::from_points(Point::new(0f32, 40f32), Point::new(10f32, 50f32)));
+        let mut role_options = RoleOptions::new();
+        role_options.entrance.min_area = Some(400f32);
+        let roles = RoleAssigner::new(&role_options).assign(&dungeon, &mut thread_rng());
+        assert!(roles.contains(&(RoomId::new(0), RoomRole::Entrance)));
+        assert!(!roles.iter().any(|&(room, _)| room == isolated));
+    }
+
+    #[test]
+    fn test_max_rooms() {
+        let dungeon = chain_dungeon();
+        let mut tag = RoleRule::new(RoomRole::Tag("lit".to_string()));
+        tag.max_rooms = Some(2);
+        let role_options = RoleOptions::new().with_rule(tag);
+        let roles = RoleAssigner::new(&role_options).assign(&dungeon, &mut thread_rng());
+        assert_eq!(2, roles.iter().filter(|&(_, role)| *role == RoomRole::Tag("lit".to_string())).count());
+    }
+}
@@ -0,0 +1,103 @@
+use roomrole::RoomRole;
+use scalar::Scalar;
+
+/// Constraints a room has to meet to receive `role`. Depth is the graph distance from
+/// the entrance and degree is the number of distinct rooms and hallways the room has a
+/// door to, so two doors onto the same hallway count once.
+pub struct RoleRule<T = f32> {
+    pub role: RoomRole,
+    pub min_area: Option<T>,
+    pub max_area: Option<T>,
+    pub min_depth: Option<usize>,
+    pub max_depth: Option<usize>,
+    pub min_degree: Option<usize>,
+    pub max_degree: Option<usize>,
+    pub max_rooms: Option<usize>,
+}
+
+impl<T: Scalar> RoleRule<T> {
+    pub fn new(role: RoomRole) -> RoleRule<T> {
+        RoleRule { 
+            role, 
+            min_area: None, 
+            max_area: None, 
+            min_depth: None, 
+            max_depth: None, 
+            min_degree: None, 
+            max_degree: None, 
+            max_rooms: None 
+        }
+    }
+
+    pub fn matches(&self, area: T, depth: Option<usize>, degree: usize) -> bool {
+        let area_matches = self.min_area.map_or(true, |min| area >= min) && self.max_area.map_or(true, |max| area <= max);
+        let depth_matches = match depth {
+            Some(depth) => self.min_depth.map_or(true, |min| depth >= min) && self.max_depth.map_or(true, |max| depth <= max),
+            None => self.min_depth.is_none() && self.max_depth.is_none()
+        };
+        let degree_matches = self.min_degree.map_or(true, |min| degree >= min) && self.max_degree.map_or(true, |max| degree <= max);
+        area_matches && depth_matches && degree_matches
+    }
+}
+
+pub struct RoleOptions<T = f32> {
+    pub entrance: RoleRule<T>,
+    pub farthest: RoomRole,
+    pub rules: Vec<RoleRule<T>>,
+}
+
+impl<T: Scalar> RoleOptions<T> {
+    /// Picks an entrance, makes the room farthest from it the boss room, marks rooms
+    /// with a single neighbour as dead ends and hides treasure in one dead end at least
+    /// two steps from the entrance.
+    pub fn new() -> RoleOptions<T> {
+        let mut dead_end = RoleRule::new(RoomRole::DeadEnd);
+        dead_end.min_degree = Some(1);
+        dead_end.max_degree = Some(1);
+        let mut treasure = RoleRule::new(RoomRole::Treasure);
+        treasure.max_degree = Some(1);
+        treasure.min_depth = Some(2);
+        treasure.max_rooms = Some(1);
+        RoleOptions { 
+            entrance: RoleRule::new(RoomRole::Entrance), 
+            farthest: RoomRole::Boss, 
+            rules: vec![dead_end, treasure] 
+        }
+    }
+
+    pub fn with_rule(mut self, rule: RoleRule<T>) -> RoleOptions<T> {
+        self.rules.push(rule);
+        self
+    }
+}
+
+impl<T: Scalar> Default for RoleOptions<T> {
+    fn default() -> RoleOptions<T> {
+        RoleOptions::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roomrole::RoomRole;
+
+    #[test]
+    fn test_matches() {
+        let mut rule = RoleRule::new(RoomRole::Treasure);
+        rule.min_area = Some(10f32);
+        rule.max_depth = Some(3);
+        rule.max_degree = Some(1);
+        assert!(rule.matches(10f32, Some(3), 1));
+        assert!(!rule.matches(9f32, Some(3), 1));
+        assert!(!rule.matches(10f32, Some(4), 1));
+        assert!(!rule.matches(10f32, None, 1));
+        assert!(!rule.matches(10f32, Some(2), 2));
+    }
+
+    #[test]
+    fn test_unreachable_without_depth() {
+        let rule = RoleRule::new(RoomRole::Tag("storage".to_string()));
+        assert!(rule.matches(1f32, None, 0));
+    }
+}
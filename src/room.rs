@@ -1,34 +1,131 @@
 use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+use adjacency::Adjacency;
 use chunk::Chunk;
+use roomrole::RoomRole;
 use std::cmp::Ordering;
+use std::fmt;
 
-pub struct Room {
-    upper_right: Point,
-    lower_left: Point
+/// Names a room by its place in `Dungeon::rooms`. Rooms are never removed or reordered,
+/// so an id stays valid for the life of the dungeon, and doors, shared walls and
+/// `Dungeon::to_json` all refer to rooms by it.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct RoomId(usize);
+
+impl RoomId {
+    pub fn new(index: usize) -> RoomId {
+        RoomId(index)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+impl fmt::Display for RoomId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "room {}", self.0)
+    }
+}
+
+pub struct Room<T = f32> {
+    rect: Rect<T>,
+    bsp_depth: usize,
+    roles: Vec<RoomRole>
 }
 
-impl Room {
-    pub fn new(chunk: Chunk) -> Room {
-        Room { upper_right: chunk.upper_right().clone(), lower_left: chunk.lower_left().clone() }
+impl<T: Scalar> Room<T> {
+    pub fn new(chunk: Chunk<T>) -> Room<T> {
+        Room { rect: *chunk.rect(), bsp_depth: chunk.depth(), roles: Vec::new() }
+    }
+
+    /// A room that did not come from splitting, like one read back from a saved layout.
+    /// It sits at depth zero of the split tree.
+    pub fn from_rect(rect: Rect<T>) -> Room<T> {
+        Room { rect, bsp_depth: 0, roles: Vec::new() }
+    }
+
+    /// The room spanned by two opposite corners, in either order.
+    pub fn from_points(a: Point<T>, b: Point<T>) -> Room<T> {
+        Room::from_rect(Rect::new(a, b))
+    }
+
+    pub fn rect(&self) -> &Rect<T> {
+        &self.rect
+    }
+
+    pub fn upper_right(&self) -> &Point<T> {
+        self.rect.upper_right()
+    }
+
+    pub fn lower_left(&self) -> &Point<T> {
+        self.rect.lower_left()
+    }
+
+    pub fn width(&self) -> T {
+        self.rect.width()
+    }
+
+    pub fn height(&self) -> T {
+        self.rect.height()
+    }
+
+    pub fn area(&self) -> T {
+        self.rect.area()
+    }
+
+    pub fn center(&self) -> Point<T> {
+        self.rect.center()
+    }
+
+    /// True when `point` lies inside the room or on its walls.
+    pub fn contains(&self, point: &Point<T>) -> bool {
+        self.rect.contains(point)
+    }
+
+    /// True when the two rooms share some floor. Rooms that only touch do not intersect.
+    pub fn intersects(&self, other: &Room<T>) -> bool {
+        self.rect.intersects(&other.rect)
+    }
+
+    pub fn bsp_depth(&self) -> usize {
+        self.bsp_depth
     }
 
-    pub fn upper_right(&self) -> &Point {
-        &self.upper_right
+    pub fn roles(&self) -> &[RoomRole] {
+        &self.roles[..]
     }
 
-    pub fn lower_left(&self) -> &Point {
-        &self.lower_left
+    pub fn has_role(&self, role: &RoomRole) -> bool {
+        self.roles.contains(role)
     }
 
-    pub fn room_overlaps(&self, other: &Room) -> bool {
-        self.lower_left.x() == other.upper_right.x() ||
-            self.lower_left.y() == other.upper_right.y() ||
-            self.upper_right.x() == other.lower_left.x() ||
-            self.upper_right.y() == other.lower_left.y()
+    pub fn add_role(&mut self, role: RoomRole) {
+        if !self.has_role(&role) {
+            self.roles.push(role);
+        }
     }
 
-    pub fn compare(&self, other: &Room) -> Ordering {
-        self.lower_left.compare_x_y(other.lower_left())
+    /// The wall this room shares with `other`, allowing `tolerance` of float error in
+    /// where the two walls lie.
+    pub fn adjacency(&self, other: &Room<T>, tolerance: T) -> Option<Adjacency<T>> {
+        self.rect.adjacency(&other.rect, tolerance)
+    }
+
+    /// Whether the rooms share a wall. This used to be true whenever any two of their
+    /// edges lined up, even far apart.
+    #[deprecated(note = "use `adjacency` or `is_adjacent`")]
+    pub fn room_overlaps(&self, other: &Room<T>) -> bool {
+        self.rect.adjacency(&other.rect, T::TOLERANCE).is_some()
+    }
+
+    pub fn is_adjacent(&self, other: &Room<T>) -> bool {
+        self.rect.is_adjacent(&other.rect)
+    }
+
+    pub fn compare(&self, other: &Room<T>) -> Ordering {
+        self.lower_left().compare_x_y(other.lower_left())
     }
 }
 
@@ -37,6 +134,12 @@ mod tests {
     use super::*;
     use point::Point;
     use chunk::{ChunkSplit, Chunk};
+    use roomrole::RoomRole;
+    use adjacency::Orientation;
+
+    fn room(lower_left: (f32, f32), upper_right: (f32, f32)) -> Room {
+        Room::new(Chunk::new(Point::new(lower_left.0, lower_left.1), Point::new(upper_right.0, upper_right.1), ChunkSplit::Vertical))
+    }
 
     #[test]
     fn test_new() {
@@ -49,4 +152,81 @@ mod tests {
         assert_eq!(22f32, room.upper_right().x());
         assert_eq!(21f32, room.upper_right().y());        
     }
+
+    #[test]
+    fn test_from_points() {
+        let room = Room::from_points(Point::new(10i32, 2i32), Point::new(4i32, 8i32));
+        assert_eq!(Point::new(4i32, 2i32), *room.lower_left());
+        assert_eq!((6, 6, 36), (room.width(), room.height(), room.area()));
+        assert_eq!(Point::new(7i32, 5i32), room.center());
+        assert_eq!(0, room.bsp_depth());
+        assert!(room.contains(&Point::new(10i32, 5i32)));
+        assert!(!room.contains(&Point::new(11i32, 5i32)));
+        assert!(room.intersects(&Room::from_points(Point::new(9i32, 7i32), Point::new(12i32, 12i32))));
+        assert!(!room.intersects(&Room::from_points(Point::new(10i32, 2i32), Point::new(12i32, 12i32))));
+    }
+
+    #[test]
+    fn test_add_role() {
+        let chunk = Chunk::new(Point::new(0f32,0f32), Point::new(10f32,10f32), ChunkSplit::Horizontal);
+        let mut room = Room::new(chunk);
+        room.add_role(RoomRole::Treasure);
+        room.add_role(RoomRole::Treasure);
+        room.add_role(RoomRole::Tag("shop".to_string()));
+        assert_eq!(2, room.roles().len());
+        assert!(room.has_role(&RoomRole::Tag("shop".to_string())));
+    }
+
+    #[test]
+    fn test_touching_rooms() {
+        let a = room((0f32, 0f32), (10f32, 10f32));
+        let b = room((4f32, 10f32), (20f32, 30f32));
+        let adjacency = a.adjacency(&b, 0.001f32).unwrap();
+        assert_eq!(Orientation::Horizontal, adjacency.orientation());
+        assert!(*adjacency.start() == Point::new(4f32, 10f32));
+        assert!(*adjacency.end() == Point::new(10f32, 10f32));
+        assert_eq!(6f32, adjacency.length());
+        assert_eq!(adjacency, b.adjacency(&a, 0.001f32).unwrap());
+    }
+
+    #[test]
+    fn test_overlapping_rooms() {
+        let a = room((0f32, 0f32), (10f32, 10f32));
+        let b = room((5f32, 5f32), (20f32, 20f32));
+        assert!(a.adjacency(&b, 0.001f32).is_none());
+        assert!(!a.is_adjacent(&room((0f32, 0f32), (10f32, 10f32))));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_room_overlaps() {
+        let a = room((0f32, 0f32), (10f32, 10f32));
+        assert!(a.room_overlaps(&room((10f32, 4f32), (20f32, 30f32))));
+        assert!(!a.room_overlaps(&room((10f32, 50f32), (20f32, 60f32))));
+    }
+
+    #[test]
+    fn test_corner_only_rooms() {
+        let a = room((0f32, 0f32), (10f32, 10f32));
+        let b = room((10f32, 10f32), (20f32, 20f32));
+        assert!(a.adjacency(&b, 0.001f32).is_none());
+    }
+
+    #[test]
+    fn test_distant_rooms() {
+        let a = room((0f32, 0f32), (10f32, 10f32));
+        let b = room((10f32, 50f32), (20f32, 60f32));
+        assert!(!a.is_adjacent(&b));
+        assert!(!a.is_adjacent(&room((30f32, 0f32), (40f32, 10f32))));
+    }
+
+    #[test]
+    fn test_adjacency_tolerance() {
+        let a = room((0f32, 0f32), (10f32, 10f32));
+        let b = room((10.0004f32, -5f32), (20f32, 5f32));
+        let adjacency = a.adjacency(&b, 0.001f32).unwrap();
+        assert_eq!(Orientation::Vertical, adjacency.orientation());
+        assert_eq!(5f32, adjacency.length());
+        assert!(a.adjacency(&b, 0.0001f32).is_none());
+    }
 }
@@ -0,0 +1,166 @@
+use door::{Door, Region};
+use room::{Room, RoomId};
+use scalar::Scalar;
+use segment::Segment;
+use std::collections::VecDeque;
+
+pub struct RoomGraph {
+    room_count: usize,
+    edges: Vec<Vec<usize>>
+}
+
+impl RoomGraph {
+    pub fn new<T: Scalar>(room_count: usize, hallway_count: usize, doors: &[Door<T>]) -> RoomGraph {
+        let mut graph = RoomGraph { room_count, edges: vec![Vec::new(); room_count + hallway_count] };
+        for door in doors {
+            graph.add_edge(door.from(), door.to());
+        }
+        graph
+    }
+
+    pub fn node(&self, region: Region) -> usize {
+        match region {
+            Region::Room(id) => id.index(),
+            Region::Hallway(idx) => self.room_count + idx
+        }
+    }
+
+    pub fn add_edge(&mut self, a: Region, b: Region) {
+        let a_node = self.node(a);
+        let b_node = self.node(b);
+        if !self.edges[a_node].contains(&b_node) {
+            self.edges[a_node].push(b_node);
+            self.edges[b_node].push(a_node);
+        }
+    }
+
+    pub fn degree(&self, region: Region) -> usize {
+        self.edges[self.node(region)].len()
+    }
+
+    /// Breadth first distances from `from` to every node, `None` when unreachable.
+    pub fn distances(&self, from: Region) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.edges.len()];
+        let start = self.node(from);
+        distances[start] = Some(0);
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[node].expect("queued node has no distance");
+            for &next in &self.edges[node] {
+                if distances[next].is_none() {
+                    distances[next] = Some(distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Labels every node with the index of the connected component it belongs to.
+    pub fn component_labels(&self) -> Vec<usize> {
+        let mut labels : Vec<Option<usize>> = vec![None; self.edges.len()];
+        let mut next_label = 0usize;
+        for start in 0..self.edges.len() {
+            if labels[start].is_some() {
+                continue;
+            }
+            labels[start] = Some(next_label);
+            let mut stack = vec![start];
+            while let Some(node) = stack.pop() {
+                for &next in &self.edges[node] {
+                    if labels[next].is_none() {
+                        labels[next] = Some(next_label);
+                        stack.push(next);
+                    }
+                }
+            }
+            next_label += 1;
+        }
+        labels.into_iter().map(|label| label.expect("node was not labeled")).collect()
+    }
+
+    pub fn region(&self, node: usize) -> Region {
+        if node < self.room_count {
+            Region::Room(RoomId::new(node))
+        } else {
+            Region::Hallway(node - self.room_count)
+        }
+    }
+
+    pub fn components(&self) -> Vec<Vec<Region>> {
+        let labels = self.component_labels();
+        let count = labels.iter().max().map_or(0, |max| max + 1);
+        let mut components = vec![Vec::new(); count];
+        for (node, &label) in labels.iter().enumerate() {
+            components[label].push(self.region(node));
+        }
+        components
+    }
+}
+
+/// Finds every pair of rooms that share a wall, along with the longest shared segment.
+pub fn shared_walls<T: Scalar>(rooms: &[Room<T>]) -> Vec<(RoomId, RoomId, Segment<T>)> {
+    let mut order : Vec<usize> = (0..rooms.len()).collect();
+    order.sort_by(|a, b| rooms[*a].lower_left().x().partial_cmp(&rooms[*b].lower_left().x()).expect("room coordinate is NaN"));
+    let mut walls = Vec::new();
+    for (position, &a) in order.iter().enumerate() {
+        for &b in &order[position + 1..] {
+            if rooms[b].lower_left().x() > rooms[a].upper_right().x() + T::TOLERANCE {
+                break;
+            }
+            if rooms[b].lower_left().y() > rooms[a].upper_right().y() + T::TOLERANCE || rooms[b].upper_right().y() < rooms[a].lower_left().y() - T::TOLERANCE {
+                continue;
+            }
+            if let Some(shared) = rooms[a].rect().shared_segment(rooms[b].rect()) {
+                walls.push((RoomId::new(a.min(b)), RoomId::new(a.max(b)), shared));
+            }
+        }
+    }
+    walls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use door::Region;
+    use point::Point;
+    use room::{Room, RoomId};
+    use chunk::{Chunk, ChunkSplit};
+
+    #[test]
+    fn test_distance() {
+        let mut graph = RoomGraph::new::<f32>(3, 1, &[]);
+        graph.add_edge(Region::Room(RoomId::new(0)), Region::Hallway(0));
+        graph.add_edge(Region::Hallway(0), Region::Room(RoomId::new(1)));
+        let distances = graph.distances(Region::Room(RoomId::new(0)));
+        assert_eq!(Some(2), distances[graph.node(Region::Room(RoomId::new(1)))]);
+        assert_eq!(None, distances[graph.node(Region::Room(RoomId::new(2)))]);
+        assert_eq!(Some(1), distances[graph.node(Region::Hallway(0))]);
+    }
+
+    #[test]
+    fn test_components() {
+        let mut graph = RoomGraph::new::<f32>(3, 1, &[]);
+        graph.add_edge(Region::Room(RoomId::new(0)), Region::Hallway(0));
+        graph.add_edge(Region::Hallway(0), Region::Room(RoomId::new(2)));
+        let components = graph.components();
+        assert_eq!(2, components.len());
+        assert_eq!(vec![Region::Room(RoomId::new(0)), Region::Room(RoomId::new(2)), Region::Hallway(0)], components[0]);
+        assert_eq!(vec![Region::Room(RoomId::new(1))], components[1]);
+    }
+
+    #[test]
+    fn test_shared_walls() {
+        let rooms = vec![
+            Room::new(Chunk::new(Point::new(0f32, 0f32), Point::new(10f32, 10f32), ChunkSplit::Vertical)),
+            Room::new(Chunk::new(Point::new(10f32, 5f32), Point::new(20f32, 10f32), ChunkSplit::Vertical)),
+            Room::new(Chunk::new(Point::new(10f32, 10f32), Point::new(20f32, 20f32), ChunkSplit::Vertical)),
+            Room::new(Chunk::new(Point::new(30f32, 0f32), Point::new(40f32, 10f32), ChunkSplit::Vertical)),
+        ];
+        let walls = shared_walls(&rooms);
+        assert_eq!(2, walls.len());
+        assert!(walls.iter().any(|wall| wall.0 == RoomId::new(0) && wall.1 == RoomId::new(1)));
+        assert!(walls.iter().any(|wall| wall.0 == RoomId::new(1) && wall.1 == RoomId::new(2)));
+    }
+}
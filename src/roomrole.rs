@@ -0,0 +1,23 @@
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RoomRole {
+    Entrance,
+    Exit,
+    Boss,
+    Treasure,
+    DeadEnd,
+    Tag(String),
+}
+
+impl RoomRole {
+    /// The role as exporters write it, which for tags is the tag itself.
+    pub fn name(&self) -> &str {
+        match *self {
+            RoomRole::Entrance => "entrance",
+            RoomRole::Exit => "exit",
+            RoomRole::Boss => "boss",
+            RoomRole::Treasure => "treasure",
+            RoomRole::DeadEnd => "dead_end",
+            RoomRole::Tag(ref tag) => tag
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use rand::distributions::range::SampleRange;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, Neg, Sub, SubAssign};
+
+/// The number type dungeon coordinates are stored in. `f32` is the default everywhere,
+/// `f64` keeps precision on very large maps and `i32` keeps every wall on whole tiles.
+/// Anything that needs a square root or a fraction is worked out in `f64` and converted
+/// back, so integer dungeons round those values to the nearest tile.
+pub trait Scalar: Copy + PartialOrd + Default + fmt::Debug + fmt::Display + SampleRange
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> + Neg<Output = Self>
+    + AddAssign + SubAssign + DivAssign {
+    const ZERO: Self;
+    const ONE: Self;
+    /// Largest difference still treated as equal when comparing coordinates.
+    const TOLERANCE: Self;
+
+    fn from_f64(value: f64) -> Self;
+
+    fn to_f64(self) -> f64;
+
+    fn abs(self) -> Self {
+        if self < Self::ZERO { -self } else { self }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if other < self { other } else { self }
+    }
+
+    fn max(self, other: Self) -> Self {
+        if other > self { other } else { self }
+    }
+
+    fn sqrt(self) -> Self {
+        Self::from_f64(self.to_f64().sqrt())
+    }
+}
+
+impl Scalar for f32 {
+    const ZERO: f32 = 0f32;
+    const ONE: f32 = 1f32;
+    const TOLERANCE: f32 = 0.001f32;
+
+    fn from_f64(value: f64) -> f32 {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Scalar for f64 {
+    const ZERO: f64 = 0f64;
+    const ONE: f64 = 1f64;
+    const TOLERANCE: f64 = 0.000001f64;
+
+    fn from_f64(value: f64) -> f64 {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Scalar for i32 {
+    const ZERO: i32 = 0;
+    const ONE: i32 = 1;
+    const TOLERANCE: i32 = 0;
+
+    fn from_f64(value: f64) -> i32 {
+        value.round() as i32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_rounding() {
+        assert_eq!(3, i32::from_f64(2.6f64));
+        assert_eq!(-3, i32::from_f64(-2.6f64));
+        assert_eq!(5, Scalar::sqrt(24i32));
+    }
+
+    #[test]
+    fn test_min_max_abs() {
+        assert_eq!(2f64, Scalar::min(2f64, 3f64));
+        assert_eq!(3f32, Scalar::max(2f32, 3f32));
+        assert_eq!(4i32, Scalar::abs(-4i32));
+    }
+}
@@ -0,0 +1,48 @@
+use point::Point;
+use rect::Rect;
+use dungeon::Dungeon;
+use scalar::Scalar;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// One square of a streamed world. `gates` holds the opening on each border, listed in
+/// the order of `Side`, where the sector's hallways run into the neighbouring sector.
+pub struct Sector<T = f32> {
+    x: i32,
+    y: i32,
+    rect: Rect<T>,
+    gates: [(Point<T>, Point<T>); 4],
+    dungeon: Dungeon<T>
+}
+
+impl<T: Scalar> Sector<T> {
+    pub fn new(x: i32, y: i32, rect: Rect<T>, gates: [(Point<T>, Point<T>); 4], dungeon: Dungeon<T>) -> Sector<T> {
+        Sector { x, y, rect, gates, dungeon }
+    }
+
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    pub fn rect(&self) -> &Rect<T> {
+        &self.rect
+    }
+
+    pub fn gate(&self, side: Side) -> (Point<T>, Point<T>) {
+        self.gates[side as usize]
+    }
+
+    pub fn dungeon(&self) -> &Dungeon<T> {
+        &self.dungeon
+    }
+}
@@ -0,0 +1,275 @@
+use point::Point;
+use scalar::Scalar;
+
+pub type Segment<T = f32> = (Point<T>, Point<T>);
+
+pub fn rect_edges<T: Scalar>(lower_left: &Point<T>, upper_right: &Point<T>) -> Vec<Segment<T>> {
+    let upper_left = Point::new(lower_left.x(), upper_right.y());
+    let lower_right = Point::new(upper_right.x(), lower_left.y());
+    vec![(*lower_left, upper_left), (upper_left, *upper_right), (lower_right, *upper_right), (*lower_left, lower_right)]
+}
+
+pub fn polygon_edges<T: Scalar>(points: &[Point<T>]) -> Vec<Segment<T>> {
+    let mut edges = Vec::with_capacity(points.len());
+    for idx in 0..points.len() {
+        edges.push((points[idx], points[(idx + 1) % points.len()]));
+    }
+    edges
+}
+
+pub fn length<T: Scalar>(segment: &Segment<T>) -> T {
+    (segment.1.x() - segment.0.x()).abs() + (segment.1.y() - segment.0.y()).abs()
+}
+
+/// Returns the part two axis aligned segments have in common when they lie on the same line.
+pub fn shared_segment<T: Scalar>(a: &Segment<T>, b: &Segment<T>) -> Option<Segment<T>> {
+    let a_vertical = (a.0.x() - a.1.x()).abs() <= T::TOLERANCE;
+    let b_vertical = (b.0.x() - b.1.x()).abs() <= T::TOLERANCE;
+    let a_horizontal = (a.0.y() - a.1.y()).abs() <= T::TOLERANCE;
+    let b_horizontal = (b.0.y() - b.1.y()).abs() <= T::TOLERANCE;
+    if a_vertical && b_vertical && (a.0.x() - b.0.x()).abs() <= T::TOLERANCE {
+        let low = a.0.y().min(a.1.y()).max(b.0.y().min(b.1.y()));
+        let high = a.0.y().max(a.1.y()).min(b.0.y().max(b.1.y()));
+        if high - low > T::TOLERANCE {
+            return Some((Point::new(a.0.x(), low), Point::new(a.0.x(), high)));
+        }
+    } else if a_horizontal && b_horizontal && (a.0.y() - b.0.y()).abs() <= T::TOLERANCE {
+        let low = a.0.x().min(a.1.x()).max(b.0.x().min(b.1.x()));
+        let high = a.0.x().max(a.1.x()).min(b.0.x().max(b.1.x()));
+        if high - low > T::TOLERANCE {
+            return Some((Point::new(low, a.0.y()), Point::new(high, a.0.y())));
+        }
+    }
+    None
+}
+
+/// Returns the longest segment shared between two sets of edges.
+pub fn longest_shared_segment<T: Scalar>(a: &[Segment<T>], b: &[Segment<T>]) -> Option<Segment<T>> {
+    let mut longest: Option<Segment<T>> = None;
+    for a_edge in a {
+        for b_edge in b {
+            if let Some(shared) = shared_segment(a_edge, b_edge) {
+                let is_longer = match longest {
+                    Some(ref current) => length(&shared) > length(current),
+                    None => true
+                };
+                if is_longer {
+                    longest = Some(shared);
+                }
+            }
+        }
+    }
+    longest
+}
+
+/// Returns a piece of `segment` at most `width` long centered on its midpoint. Integer
+/// segments keep the full width and put the odd unit on the upper side.
+pub fn centered<T: Scalar>(segment: &Segment<T>, width: T) -> Segment<T> {
+    let segment_length = length(segment);
+    let width = width.min(segment_length);
+    let offset = (segment_length - width) / (T::ONE + T::ONE);
+    if (segment.0.x() - segment.1.x()).abs() <= T::TOLERANCE {
+        let start = segment.0.y().min(segment.1.y()) + offset;
+        (Point::new(segment.0.x(), start), Point::new(segment.0.x(), start + width))
+    } else {
+        let start = segment.0.x().min(segment.1.x()) + offset;
+        (Point::new(start, segment.0.y()), Point::new(start + width, segment.0.y()))
+    }
+}
+
+pub fn distance_to_segment<T: Scalar>(point: &Point<T>, segment: &Segment<T>) -> T {
+    let (point, start, end) = (point.cast::<f64>(), segment.0.cast::<f64>(), segment.1.cast::<f64>());
+    let dx = end.x() - start.x();
+    let dy = end.y() - start.y();
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0f64 {
+        (((point.x() - start.x()) * dx + (point.y() - start.y()) * dy) / length_squared).clamp(0f64, 1f64)
+    } else {
+        0f64
+    };
+    let closest_x = start.x() + t * dx - point.x();
+    let closest_y = start.y() + t * dy - point.y();
+    T::from_f64((closest_x * closest_x + closest_y * closest_y).sqrt())
+}
+
+/// Even-odd test for whether `point` lies inside the polygon outlined by `points`.
+pub fn polygon_contains<T: Scalar>(points: &[Point<T>], point: &Point<T>) -> bool {
+    let mut inside = false;
+    for (start, end) in polygon_edges(points) {
+        if (start.y() > point.y()) != (end.y() > point.y()) {
+            let (start, end) = (start.cast::<f64>(), end.cast::<f64>());
+            let crossing_x = start.x() + (point.y().to_f64() - start.y()) / (end.y() - start.y()) * (end.x() - start.x());
+            if point.x().to_f64() < crossing_x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// True when no two edges of the polygon outlined by `points` cross or touch, other than
+/// neighbouring edges meeting at their shared corner.
+pub fn polygon_is_simple<T: Scalar>(points: &[Point<T>]) -> bool {
+    let edges = polygon_edges(points);
+    let count = edges.len();
+    for a in 0..count {
+        for b in (a + 1)..count {
+            if b == a + 1 || (a == 0 && b == count - 1) {
+                continue;
+            }
+            if segments_touch(&edges[a], &edges[b]) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+pub fn segments_touch<T: Scalar>(a: &Segment<T>, b: &Segment<T>) -> bool {
+    let (p, q, r, s) = (a.0.cast::<f64>(), a.1.cast::<f64>(), b.0.cast::<f64>(), b.1.cast::<f64>());
+    let orientation = |a: &Point<f64>, b: &Point<f64>, c: &Point<f64>| {
+        let turn = (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x());
+        if turn.abs() <= 1e-9 { 0 } else if turn > 0f64 { 1 } else { -1 }
+    };
+    let on_segment = |a: &Point<f64>, b: &Point<f64>, c: &Point<f64>| {
+        c.x() >= a.x().min(b.x()) && c.x() <= a.x().max(b.x()) && c.y() >= a.y().min(b.y()) && c.y() <= a.y().max(b.y())
+    };
+    let (o1, o2, o3, o4) = (orientation(&p, &q, &r), orientation(&p, &q, &s), orientation(&r, &s, &p), orientation(&r, &s, &q));
+    (o1 != o2 && o3 != o4) ||
+        (o1 == 0 && on_segment(&p, &q, &r)) || (o2 == 0 && on_segment(&p, &q, &s)) ||
+        (o3 == 0 && on_segment(&r, &s, &p)) || (o4 == 0 && on_segment(&r, &s, &q))
+}
+
+/// Cuts a rectilinear polygon, given by all of its edges, into rectangles by slicing it at
+/// every vertex x coordinate, then joins neighbouring slices that cover the same y range.
+pub fn decompose<T: Scalar>(edges: &[Segment<T>]) -> Vec<(Point<T>, Point<T>)> {
+    let mut xs : Vec<T> = edges.iter().map(|edge| edge.0.x()).collect();
+    xs.sort_by(|a, b| a.partial_cmp(b).expect("hallway coordinate is NaN"));
+    xs.dedup_by(|a, b| (*a - *b).abs() <= T::TOLERANCE);
+    let horizontal_edges : Vec<Segment<T>> = edges.iter().cloned()
+        .filter(|edge| (edge.0.y() - edge.1.y()).abs() <= T::TOLERANCE)
+        .collect();
+    let mut rects : Vec<(Point<T>, Point<T>)> = Vec::new();
+    for slab in xs.windows(2) {
+        let middle = (slab[0].to_f64() + slab[1].to_f64()) / 2f64;
+        let mut ys : Vec<T> = horizontal_edges.iter()
+            .filter(|edge| edge.0.x().min(edge.1.x()).to_f64() < middle && edge.0.x().max(edge.1.x()).to_f64() > middle)
+            .map(|edge| edge.0.y())
+            .collect();
+        ys.sort_by(|a, b| a.partial_cmp(b).expect("hallway coordinate is NaN"));
+        for pair in ys.chunks(2) {
+            if pair.len() < 2 {
+                continue;
+            }
+            let extends = rects.iter().position(|rect| {
+                (rect.1.x() - slab[0]).abs() <= T::TOLERANCE && (rect.0.y() - pair[0]).abs() <= T::TOLERANCE && (rect.1.y() - pair[1]).abs() <= T::TOLERANCE
+            });
+            match extends {
+                Some(idx) => rects[idx].1.set_x(slab[1]),
+                None => rects.push((Point::new(slab[0], pair[0]), Point::new(slab[1], pair[1])))
+            }
+        }
+    }
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+
+    #[test]
+    fn test_shared_vertical_segment() {
+        let a = (Point::new(10f32, 0f32), Point::new(10f32, 10f32));
+        let b = (Point::new(10f32, 5f32), Point::new(10f32, 20f32));
+        let shared = shared_segment(&a, &b).unwrap();
+        assert!(shared.0 == Point::new(10f32, 5f32));
+        assert!(shared.1 == Point::new(10f32, 10f32));
+    }
+
+    #[test]
+    fn test_corner_is_not_shared() {
+        let a = (Point::new(0f32, 10f32), Point::new(10f32, 10f32));
+        let b = (Point::new(10f32, 10f32), Point::new(20f32, 10f32));
+        assert!(shared_segment(&a, &b).is_none());
+    }
+
+    #[test]
+    fn test_parallel_is_not_shared() {
+        let a = (Point::new(0f32, 10f32), Point::new(10f32, 10f32));
+        let b = (Point::new(0f32, 11f32), Point::new(10f32, 11f32));
+        assert!(shared_segment(&a, &b).is_none());
+    }
+
+    #[test]
+    fn test_centered() {
+        let segment = (Point::new(0f32, 4f32), Point::new(10f32, 4f32));
+        let door = centered(&segment, 2f32);
+        assert!(door.0 == Point::new(4f32, 4f32));
+        assert!(door.1 == Point::new(6f32, 4f32));
+    }
+
+    #[test]
+    fn test_distance_to_segment() {
+        let segment = (Point::new(0f32, 0f32), Point::new(10f32, 0f32));
+        assert_eq!(3f32, distance_to_segment(&Point::new(5f32, 3f32), &segment));
+        assert_eq!(5f32, distance_to_segment(&Point::new(13f32, 4f32), &segment));
+    }
+
+    #[test]
+    fn test_polygon_contains() {
+        let points = vec![
+            Point::new(0f32, 0f32), Point::new(0f32, 10f32), Point::new(2f32, 10f32),
+            Point::new(2f32, 2f32), Point::new(10f32, 2f32), Point::new(10f32, 0f32)
+        ];
+        assert!(polygon_contains(&points, &Point::new(1f32, 5f32)));
+        assert!(polygon_contains(&points, &Point::new(5f32, 1f32)));
+        assert!(!polygon_contains(&points, &Point::new(5f32, 5f32)));
+    }
+
+    #[test]
+    fn test_decompose() {
+        let points = vec![
+            Point::new(0f32, 0f32), Point::new(0f32, 10f32), Point::new(2f32, 10f32),
+            Point::new(2f32, 2f32), Point::new(10f32, 2f32), Point::new(10f32, 0f32)
+        ];
+        let rects = decompose(&polygon_edges(&points));
+        assert_eq!(2, rects.len());
+        assert!(rects[0].0 == Point::new(0f32, 0f32) && rects[0].1 == Point::new(2f32, 10f32));
+        assert!(rects[1].0 == Point::new(2f32, 0f32) && rects[1].1 == Point::new(10f32, 2f32));
+    }
+
+    #[test]
+    fn test_polygon_is_simple() {
+        let square = vec![Point::new(0f32, 0f32), Point::new(0f32, 4f32), Point::new(4f32, 4f32), Point::new(4f32, 0f32)];
+        assert!(polygon_is_simple(&square));
+        let bowtie = vec![Point::new(0f32, 0f32), Point::new(4f32, 4f32), Point::new(4f32, 0f32), Point::new(0f32, 4f32)];
+        assert!(!polygon_is_simple(&bowtie));
+        let pinched = vec![
+            Point::new(0i32, 0i32), Point::new(0i32, 4i32), Point::new(2i32, 4i32), Point::new(2i32, 2i32),
+            Point::new(4i32, 4i32), Point::new(6i32, 4i32), Point::new(6i32, 0i32), Point::new(2i32, 0i32), Point::new(2i32, 2i32)
+        ];
+        assert!(!polygon_is_simple(&pinched));
+    }
+
+    #[test]
+    fn test_integer_centered() {
+        let segment = (Point::new(3i32, 0i32), Point::new(3i32, 10i32));
+        let door = centered(&segment, 3);
+        assert!(door.0 == Point::new(3i32, 3i32));
+        assert!(door.1 == Point::new(3i32, 6i32));
+        assert_eq!(2, distance_to_segment(&Point::new(1i32, 4i32), &segment));
+    }
+
+    #[test]
+    fn test_integer_decompose() {
+        let points = vec![
+            Point::new(0i32, 0i32), Point::new(0i32, 10i32), Point::new(1i32, 10i32),
+            Point::new(1i32, 1i32), Point::new(10i32, 1i32), Point::new(10i32, 0i32)
+        ];
+        assert!(polygon_contains(&points, &Point::new(0i32, 5i32)));
+        let rects = decompose(&polygon_edges(&points));
+        assert_eq!(2, rects.len());
+        assert!(rects[1].0 == Point::new(1i32, 0i32) && rects[1].1 == Point::new(10i32, 1i32));
+    }
+}
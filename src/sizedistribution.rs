@@ -0,0 +1,209 @@
+use room::Room;
+use scalar::Scalar;
+
+/// Rooms up to `max_area`, or of any size when it is `None`, and how many of them the
+/// splitter should aim for.
+#[derive(Clone, Copy, Debug)]
+pub struct SizeBucket<T = f32> {
+    pub max_area: Option<T>,
+    pub count: usize,
+}
+
+impl<T: Scalar> SizeBucket<T> {
+    pub fn new(max_area: Option<T>, count: usize) -> SizeBucket<T> {
+        SizeBucket { max_area, count }
+    }
+}
+
+/// A target histogram of room areas. A room falls in the first bucket large enough to
+/// hold it, and the total of all counts is the target room count.
+pub struct SizeDistribution<T = f32> {
+    buckets: Vec<SizeBucket<T>>
+}
+
+impl<T: Scalar> SizeDistribution<T> {
+    pub fn new(mut buckets: Vec<SizeBucket<T>>) -> SizeDistribution<T> {
+        buckets.sort_by(|a, b| match (a.max_area, b.max_area) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).expect("bucket area is NaN"),
+            (a, b) => a.is_none().cmp(&b.is_none())
+        });
+        SizeDistribution { buckets }
+    }
+
+    /// Aims for `count` rooms of any size.
+    pub fn with_room_count(count: usize) -> SizeDistribution<T> {
+        SizeDistribution::new(vec![SizeBucket::new(None, count)])
+    }
+
+    pub fn buckets(&self) -> &[SizeBucket<T>] {
+        &self.buckets[..]
+    }
+
+    pub fn room_count(&self) -> usize {
+        self.buckets.iter().map(|bucket| bucket.count).sum()
+    }
+
+    pub fn bucket(&self, area: T) -> Option<usize> {
+        self.buckets.iter().position(|bucket| bucket.max_area.map_or(true, |max_area| area <= max_area))
+    }
+
+    /// Rooms per bucket. Rooms too large for every bucket are left out.
+    pub fn histogram(&self, rooms: &[Room<T>]) -> Vec<usize> {
+        let mut histogram = vec![0usize; self.buckets.len()];
+        for room in rooms {
+            if let Some(bucket) = self.bucket(room.rect().area()) {
+                histogram[bucket] += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Chance that a chunk of `area` stops splitting and becomes a room. `leaves` counts
+    /// the rooms made so far together with the chunks still waiting, this one included,
+    /// and `spare_area` is the area of the other waiting chunks.
+    ///
+    /// Once there are as many leaves as the target every chunk stops. Before that a chunk
+    /// only stops when splitting it would leave its bucket, and then more readily the
+    /// more rooms the bucket is still missing. It never stops while the spare area is too
+    /// small to hold twice the smallest size of every room still missing, since split
+    /// pieces can end up anywhere between one and two times the minimum.
+    pub fn stop_chance(&self, area: T, histogram: &[usize], leaves: usize, spare_area: T, min_area: T) -> f64 {
+        if leaves >= self.room_count() {
+            return 1f64;
+        }
+        let bucket = match self.bucket(area) {
+            Some(bucket) => bucket,
+            None => return 0f64
+        };
+        let mut needed_area = T::ZERO;
+        let mut bucket_lower = min_area;
+        let mut lower = min_area;
+        for (idx, size_bucket) in self.buckets.iter().enumerate() {
+            let mut missing = size_bucket.count.saturating_sub(histogram[idx]);
+            if idx == bucket {
+                missing = missing.saturating_sub(1);
+                bucket_lower = lower;
+            }
+            needed_area += (lower + lower) * T::from_f64(missing as f64);
+            if let Some(max_area) = size_bucket.max_area {
+                lower = lower.max(max_area);
+            }
+        }
+        if needed_area > spare_area || area > bucket_lower + bucket_lower {
+            return 0f64;
+        }
+        let missing = self.buckets[bucket].count.saturating_sub(histogram[bucket]) as f64;
+        missing / (missing + 1f64)
+    }
+}
+
+/// How one bucket of a `SizeDistribution` turned out.
+#[derive(Clone, Copy, Debug)]
+pub struct BucketReport<T = f32> {
+    pub max_area: Option<T>,
+    pub target: usize,
+    pub actual: usize,
+}
+
+/// How closely generated rooms match a size distribution and aspect limit.
+pub struct SizeReport<T = f32> {
+    buckets: Vec<BucketReport<T>>,
+    room_count: usize,
+    target_room_count: usize,
+    aspect_violations: usize
+}
+
+impl<T: Scalar> SizeReport<T> {
+    /// Without a distribution every room goes in one bucket aiming for the rooms there are.
+    pub fn new(distribution: Option<&SizeDistribution<T>>, max_aspect: Option<f32>, rooms: &[Room<T>]) -> SizeReport<T> {
+        let buckets = match distribution {
+            Some(distribution) => distribution.buckets().iter().zip(distribution.histogram(rooms))
+                .map(|(bucket, actual)| BucketReport { max_area: bucket.max_area, target: bucket.count, actual })
+                .collect(),
+            None => vec![BucketReport { max_area: None, target: rooms.len(), actual: rooms.len() }]
+        };
+        let target_room_count = buckets.iter().map(|bucket: &BucketReport<T>| bucket.target).sum();
+        let aspect_violations = match max_aspect {
+            Some(max_aspect) => rooms.iter().filter(|room| room.rect().aspect_ratio() > max_aspect as f64 + T::TOLERANCE.to_f64()).count(),
+            None => 0
+        };
+        SizeReport { buckets, room_count: rooms.len(), target_room_count, aspect_violations }
+    }
+
+    pub fn buckets(&self) -> &[BucketReport<T>] {
+        &self.buckets[..]
+    }
+
+    pub fn room_count(&self) -> usize {
+        self.room_count
+    }
+
+    pub fn target_room_count(&self) -> usize {
+        self.target_room_count
+    }
+
+    /// Rooms whose long side exceeds the short one by more than the aspect limit.
+    pub fn aspect_violations(&self) -> usize {
+        self.aspect_violations
+    }
+
+    /// Share of rooms that landed in a bucket still wanting them, from 0 to 1. Rooms too
+    /// large for any bucket count against it.
+    pub fn accuracy(&self) -> f64 {
+        let matched : usize = self.buckets.iter().map(|bucket| bucket.target.min(bucket.actual)).sum();
+        let total = self.target_room_count.max(self.room_count);
+        if total == 0 {
+            1f64
+        } else {
+            matched as f64 / total as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use chunk::{Chunk, ChunkSplit};
+
+    fn room(width: f32, height: f32) -> Room {
+        Room::new(Chunk::new(Point::new(0f32, 0f32), Point::new(width, height), ChunkSplit::Vertical))
+    }
+
+    fn distribution() -> SizeDistribution {
+        SizeDistribution::new(vec![SizeBucket::new(None, 1), SizeBucket::new(Some(400f32), 2), SizeBucket::new(Some(100f32), 3)])
+    }
+
+    #[test]
+    fn test_buckets_are_sorted() {
+        let distribution = distribution();
+        assert_eq!(Some(0), distribution.bucket(100f32));
+        assert_eq!(Some(1), distribution.bucket(250f32));
+        assert_eq!(Some(2), distribution.bucket(1000f32));
+        assert_eq!(6, distribution.room_count());
+    }
+
+    #[test]
+    fn test_stop_chance() {
+        let distribution = distribution();
+        assert_eq!(0.75f64, distribution.stop_chance(15f32, &[0, 0, 0], 2, 5000f32, 10f32));
+        assert_eq!(0f64, distribution.stop_chance(50f32, &[0, 0, 0], 2, 5000f32, 10f32));
+        assert_eq!(0f64, distribution.stop_chance(700f32, &[0, 0, 1], 2, 5000f32, 10f32));
+        assert_eq!(1f64, distribution.stop_chance(1000f32, &[0, 0, 0], 6, 0f32, 10f32));
+        assert_eq!(0f64, SizeDistribution::new(vec![SizeBucket::new(Some(10f32), 4)]).stop_chance(20f32, &[0], 1, 100f32, 10f32));
+        // Two small and two medium rooms still need 2 * (2 * 10 + 2 * 100) of spare area.
+        assert_eq!(0.5f64, distribution.stop_chance(700f32, &[1, 0, 0], 2, 440f32, 10f32));
+        assert_eq!(0f64, distribution.stop_chance(700f32, &[1, 0, 0], 2, 439f32, 10f32));
+        assert_eq!(0f64, SizeDistribution::with_room_count(5).stop_chance(15f32, &[0], 1, 0f32, 10f32));
+    }
+
+    #[test]
+    fn test_report() {
+        let rooms = vec![room(10f32, 10f32), room(5f32, 5f32), room(20f32, 15f32), room(40f32, 15f32)];
+        let report = SizeReport::new(Some(&distribution()), Some(2f32), &rooms);
+        let actual : Vec<usize> = report.buckets().iter().map(|bucket| bucket.actual).collect();
+        assert_eq!(vec![2, 1, 1], actual);
+        assert_eq!(1, report.aspect_violations());
+        assert_eq!(4f64 / 6f64, report.accuracy());
+    }
+}
@@ -0,0 +1,243 @@
+use dungeon::Dungeon;
+use door::Region;
+use room::RoomId;
+use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+
+struct Entry<T> {
+    region: Region,
+    rect: Rect<T>
+}
+
+/// A uniform grid over the dungeon bounds. Rooms are stored as their rectangle and
+/// hallways as the rectangles they decompose into, so every query is a rectangle test
+/// against the few entries sharing a cell.
+pub struct SpatialIndex<T = f32> {
+    entries: Vec<Entry<T>>,
+    lower_left: Point<T>,
+    cell_size: f64,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>
+}
+
+impl<T: Scalar> SpatialIndex<T> {
+    pub fn new(dungeon: &Dungeon<T>) -> SpatialIndex<T> {
+        let mut entries = Vec::new();
+        for id in dungeon.room_ids() {
+            entries.push(Entry { region: Region::Room(id), rect: *dungeon.room(id).rect() });
+        }
+        for (idx, hallway) in dungeon.hallways().iter().enumerate() {
+            for rect in hallway.rects() {
+                entries.push(Entry { region: Region::Hallway(idx), rect });
+            }
+        }
+        if entries.is_empty() {
+            return SpatialIndex { entries, lower_left: Point::default(), cell_size: 1f64, columns: 0, rows: 0, cells: Vec::new() };
+        }
+
+        let bounds = entries.iter().fold(entries[0].rect, |bounds, entry| bounds.union(&entry.rect));
+        let lower_left = *bounds.lower_left();
+        // Aim for roughly one entry per cell.
+        let width = bounds.width().to_f64();
+        let height = bounds.height().to_f64();
+        let cell_size = (width * height / entries.len() as f64).sqrt().max(1f64);
+        let columns = (width / cell_size).ceil().max(1f64) as usize;
+        let rows = (height / cell_size).ceil().max(1f64) as usize;
+
+        let mut index = SpatialIndex { entries: Vec::new(), lower_left, cell_size, columns, rows, cells: vec![Vec::new(); columns * rows] };
+        for (idx, entry) in entries.iter().enumerate() {
+            let (first_column, first_row) = index.cell(entry.rect.lower_left());
+            let (last_column, last_row) = index.cell(entry.rect.upper_right());
+            for row in first_row..(last_row + 1) {
+                for column in first_column..(last_column + 1) {
+                    index.cells[row * columns + column].push(idx);
+                }
+            }
+        }
+        index.entries = entries;
+        index
+    }
+
+    /// The room or hallway containing `point`. Points on a wall between two regions
+    /// resolve to a room.
+    pub fn region_at(&self, point: &Point<T>) -> Option<Region> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        let (column, row) = self.cell(point);
+        self.cells[row * self.columns + column].iter()
+            .map(|&idx| &self.entries[idx])
+            .find(|entry| entry.rect.contains(point))
+            .map(|entry| entry.region)
+    }
+
+    /// Ids of the rooms overlapping `rect`, in ascending order.
+    pub fn rooms_in(&self, rect: &Rect<T>) -> Vec<RoomId> {
+        if self.cells.is_empty() {
+            return Vec::new();
+        }
+        let (first_column, first_row) = self.cell(rect.lower_left());
+        let (last_column, last_row) = self.cell(rect.upper_right());
+        let mut rooms = Vec::new();
+        for row in first_row..(last_row + 1) {
+            for column in first_column..(last_column + 1) {
+                for &idx in &self.cells[row * self.columns + column] {
+                    let entry = &self.entries[idx];
+                    if let Region::Room(room) = entry.region {
+                        if entry.rect.intersects(rect) {
+                            rooms.push(room);
+                        }
+                    }
+                }
+            }
+        }
+        rooms.sort();
+        rooms.dedup();
+        rooms
+    }
+
+    /// The room closest to `point`, measured to the nearest point of its rectangle. Grid
+    /// rings are searched outwards until no unvisited cell can hold a closer room.
+    pub fn nearest_room(&self, point: &Point<T>) -> Option<RoomId> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        let (column, row) = self.cell(point);
+        let mut nearest: Option<(T, RoomId)> = None;
+        for ring in 0..self.columns.max(self.rows) {
+            for (ring_column, ring_row) in self.ring(column, row, ring) {
+                for &idx in &self.cells[ring_row * self.columns + ring_column] {
+                    let entry = &self.entries[idx];
+                    if let Region::Room(room) = entry.region {
+                        let distance = entry.rect.distance(point);
+                        if nearest.map_or(true, |(best, best_room)| distance < best || (distance == best && room < best_room)) {
+                            nearest = Some((distance, room));
+                        }
+                    }
+                }
+            }
+            if let Some((best, _)) = nearest {
+                if best.to_f64() <= ring as f64 * self.cell_size {
+                    break;
+                }
+            }
+        }
+        nearest.map(|(_, room)| room)
+    }
+
+    /// Grid cell holding `point`, clamped to the grid.
+    fn cell(&self, point: &Point<T>) -> (usize, usize) {
+        let column = ((point.x() - self.lower_left.x()).to_f64() / self.cell_size).floor().max(0f64) as usize;
+        let row = ((point.y() - self.lower_left.y()).to_f64() / self.cell_size).floor().max(0f64) as usize;
+        (column.min(self.columns - 1), row.min(self.rows - 1))
+    }
+
+    /// Cells exactly `ring` steps away from the given cell, in either direction.
+    fn ring(&self, column: usize, row: usize, ring: usize) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        let first_column = column.saturating_sub(ring);
+        let last_column = (column + ring).min(self.columns - 1);
+        let first_row = row.saturating_sub(ring);
+        let last_row = (row + ring).min(self.rows - 1);
+        for ring_row in first_row..(last_row + 1) {
+            for ring_column in first_column..(last_column + 1) {
+                let steps = (ring_column as i64 - column as i64).abs().max((ring_row as i64 - row as i64).abs());
+                if steps as usize == ring {
+                    cells.push((ring_column, ring_row));
+                }
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use chunk::{Chunk, ChunkSplit};
+    use hallway::Hallway;
+    use room::Room;
+    use dungeonbuilder::DungeonBuilder;
+    use dimensionoptions::DimensionOptions;
+    use hallwayoptions::HallwayOptions;
+    use scalar::Scalar;
+    use segment;
+
+    fn room(lower_left: (f32, f32), upper_right: (f32, f32)) -> Room {
+        Room::new(Chunk::new(Point::new(lower_left.0, lower_left.1), Point::new(upper_right.0, upper_right.1), ChunkSplit::Vertical))
+    }
+
+    fn dungeon() -> Dungeon {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(room((0f32, 0f32), (10f32, 10f32)));
+        dungeon.add_room(room((14f32, 0f32), (30f32, 10f32)));
+        dungeon.add_room(room((0f32, 40f32), (30f32, 50f32)));
+        dungeon.add_hallways(vec![Hallway::new(vec![
+            Point::new(0f32, 10f32), Point::new(30f32, 10f32), Point::new(30f32, 14f32),
+            Point::new(4f32, 14f32), Point::new(4f32, 40f32), Point::new(0f32, 40f32)
+        ])]);
+        dungeon
+    }
+
+    #[test]
+    fn test_region_at() {
+        let index = SpatialIndex::new(&dungeon());
+        assert_eq!(Some(Region::Room(RoomId::new(0))), index.region_at(&Point::new(5f32, 5f32)));
+        assert_eq!(Some(Region::Room(RoomId::new(2))), index.region_at(&Point::new(20f32, 45f32)));
+        assert_eq!(Some(Region::Hallway(0)), index.region_at(&Point::new(2f32, 30f32)));
+        assert_eq!(Some(Region::Hallway(0)), index.region_at(&Point::new(25f32, 12f32)));
+        assert_eq!(None, index.region_at(&Point::new(12f32, 5f32)));
+        assert_eq!(None, index.region_at(&Point::new(20f32, 30f32)));
+        assert_eq!(None, index.region_at(&Point::new(-5f32, 5f32)));
+    }
+
+    #[test]
+    fn test_rooms_in() {
+        let index = SpatialIndex::new(&dungeon());
+        assert_eq!(vec![RoomId::new(0), RoomId::new(1)], index.rooms_in(&Rect::new(Point::new(8f32, 2f32), Point::new(16f32, 4f32))));
+        assert_eq!(vec![RoomId::new(2)], index.rooms_in(&Rect::new(Point::new(10f32, 30f32), Point::new(100f32, 100f32))));
+        assert!(index.rooms_in(&Rect::new(Point::new(11f32, 1f32), Point::new(13f32, 3f32))).is_empty());
+    }
+
+    #[test]
+    fn test_nearest_room() {
+        let index = SpatialIndex::new(&dungeon());
+        assert_eq!(Some(RoomId::new(0)), index.nearest_room(&Point::new(5f32, 5f32)));
+        assert_eq!(Some(RoomId::new(1)), index.nearest_room(&Point::new(13f32, 5f32)));
+        assert_eq!(Some(RoomId::new(2)), index.nearest_room(&Point::new(20f32, 30f32)));
+        assert_eq!(Some(RoomId::new(1)), index.nearest_room(&Point::new(200f32, -100f32)));
+        assert_eq!(None, SpatialIndex::new(&Dungeon::new()).nearest_room(&Point::new(0f32, 0f32)));
+    }
+
+    #[test]
+    fn test_shared_across_threads() {
+        let dungeon = ::std::sync::Arc::new(dungeon());
+        let shared = dungeon.clone();
+        let found = ::std::thread::spawn(move || shared.region_at(&Point::new(5f32, 5f32))).join().unwrap();
+        assert_eq!(Some(Region::Room(RoomId::new(0))), found);
+        assert_eq!(Some(RoomId::new(2)), dungeon.nearest_room(&Point::new(20f32, 30f32)));
+    }
+
+    #[test]
+    fn test_matches_linear_scan() {
+        let dungeon = DungeonBuilder::new()
+            .in_area(Point::new(0f32,0f32), Point::new(200f32,200f32))
+            .with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+            .with_hallway_options(HallwayOptions::new(15f32, 0f32, 3f32, 5f32))
+            .build();
+        let index = SpatialIndex::new(&dungeon);
+        for step in 0..400 {
+            let point = Point::new((step % 20) as f32 * 10.5f32 - 5f32, (step / 20) as f32 * 10.5f32 - 5f32);
+            let nearest = dungeon.rooms().iter().map(|room| room.rect().distance(&point)).fold(f32::MAX, f32::min);
+            let found = index.nearest_room(&point).expect("dungeon has rooms");
+            assert_eq!(nearest, dungeon.room(found).rect().distance(&point));
+            if let Some(Region::Hallway(idx)) = index.region_at(&point) {
+                let hallway = &dungeon.hallways()[idx];
+                assert!(hallway.contains(&point) || hallway.edges().iter().any(|edge| segment::distance_to_segment(&point, edge) <= f32::TOLERANCE));
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use dungeon::Dungeon;
+use door::Region;
+use room::RoomId;
+use point::Point;
+use spawnpoint::SpawnPoint;
+use spawnoptions::{SpawnOptions, SpawnTarget};
+use scalar::Scalar;
+use segment::{self, Segment};
+use rand::Rng;
+
+const MAX_ATTEMPTS: usize = 30;
+
+pub struct SpawnBuilder<'a, T: 'a> {
+    spawn_options: &'a SpawnOptions<T>
+}
+
+impl<'a, T: Scalar> SpawnBuilder<'a, T> {
+    pub fn new(spawn_options: &'a SpawnOptions<T>) -> SpawnBuilder<'a, T> {
+        SpawnBuilder { spawn_options }
+    }
+
+    pub fn place<R: Rng>(&self, dungeon: &Dungeon<T>, rng: &mut R) -> Vec<SpawnPoint<T>> {
+        let mut spawns = Vec::new();
+        for (idx, room) in dungeon.rooms().iter().enumerate() {
+            let outline = room.rect().corners().to_vec();
+            let kinds : Vec<(&str, f32)> = self.spawn_options.densities.iter()
+                .filter(|density| match density.target {
+                    SpawnTarget::Room => true,
+                    SpawnTarget::Role(ref role) => room.has_role(role),
+                    SpawnTarget::Hallway => false
+                })
+                .map(|density| (&density.kind[..], density.per_area))
+                .collect();
+            spawns.extend(self.place_in(dungeon, Region::Room(RoomId::new(idx)), &outline, &[], &kinds, rng));
+        }
+        let kinds : Vec<(&str, f32)> = self.spawn_options.densities.iter()
+            .filter(|density| density.target == SpawnTarget::Hallway)
+            .map(|density| (&density.kind[..], density.per_area))
+            .collect();
+        for (idx, hallway) in dungeon.hallways().iter().enumerate() {
+            spawns.extend(self.place_in(dungeon, Region::Hallway(idx), hallway.points(), hallway.holes(), &kinds, rng));
+        }
+        spawns
+    }
+
+    fn place_in<R: Rng>(&self, dungeon: &Dungeon<T>, region: Region, outline: &[Point<T>], holes: &[Vec<Point<T>>], kinds: &[(&str, f32)], rng: &mut R) -> Vec<SpawnPoint<T>> {
+        let mut spawns = Vec::new();
+        if kinds.is_empty() {
+            return spawns;
+        }
+        let mut edges = segment::polygon_edges(outline);
+        for hole in holes {
+            edges.extend(segment::polygon_edges(hole));
+        }
+        let doors : Vec<Segment<T>> = dungeon.doors().iter()
+            .filter(|door| door.from() == region || door.to() == region)
+            .map(|door| (*door.start(), *door.end()))
+            .collect();
+        let area = holes.iter().fold(polygon_area(outline), |area, hole| area - polygon_area(hole));
+        let mut placed : Vec<Point<T>> = Vec::new();
+        for &(kind, per_area) in kinds {
+            let expected = per_area as f64 * area;
+            let mut count = expected.floor() as usize;
+            if rng.gen::<f64>() < expected - expected.floor() {
+                count += 1;
+            }
+            for _ in 0..count {
+                match self.sample(outline, holes, &edges, &doors, &placed, rng) {
+                    Some(position) => {
+                        placed.push(position);
+                        spawns.push(SpawnPoint::new(position, kind.to_string(), region));
+                    },
+                    None => break
+                }
+            }
+        }
+        spawns
+    }
+
+    /// Dart throwing: a candidate is kept only when it is far enough from the walls, the
+    /// doors and every point already placed in the region.
+    fn sample<R: Rng>(&self, outline: &[Point<T>], holes: &[Vec<Point<T>>], edges: &[Segment<T>], doors: &[Segment<T>], placed: &[Point<T>], rng: &mut R) -> Option<Point<T>> {
+        let margin = self.spawn_options.wall_margin;
+        let min_x = outline.iter().fold(outline[0].x(), |min, point| min.min(point.x())) + margin;
+        let max_x = outline.iter().fold(outline[0].x(), |max, point| max.max(point.x())) - margin;
+        let min_y = outline.iter().fold(outline[0].y(), |min, point| min.min(point.y())) + margin;
+        let max_y = outline.iter().fold(outline[0].y(), |max, point| max.max(point.y())) - margin;
+        if min_x >= max_x || min_y >= max_y {
+            return None;
+        }
+        for _ in 0..MAX_ATTEMPTS {
+            let candidate = Point::new(rng.gen_range(min_x, max_x), rng.gen_range(min_y, max_y));
+            if !segment::polygon_contains(outline, &candidate) || holes.iter().any(|hole| segment::polygon_contains(hole, &candidate)) {
+                continue;
+            }
+            if edges.iter().any(|edge| segment::distance_to_segment(&candidate, edge) < margin) {
+                continue;
+            }
+            if doors.iter().any(|door| segment::distance_to_segment(&candidate, door) < self.spawn_options.door_clearance) {
+                continue;
+            }
+            if placed.iter().any(|point| point.distance(&candidate) < self.spawn_options.min_distance) {
+                continue;
+            }
+            return Some(candidate);
+        }
+        None
+    }
+}
+
+fn polygon_area<T: Scalar>(points: &[Point<T>]) -> f64 {
+    let twice_area = segment::polygon_edges(points).iter()
+        .fold(0f64, |sum, &(start, end)| sum + start.x().to_f64() * end.y().to_f64() - end.x().to_f64() * start.y().to_f64());
+    twice_area.abs() / 2f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use room::Room;
+    use dungeon::Dungeon;
+    use door::{Door, DoorKind, Region};
+    use roomrole::RoomRole;
+    use spawnoptions::{SpawnDensity, SpawnOptions, SpawnTarget};
+    use chunk::{Chunk, ChunkSplit};
+    use rand::thread_rng;
+
+    fn dungeon() -> Dungeon {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0f32, 0f32), Point::new(20f32, 20f32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(20f32, 0f32), Point::new(40f32, 20f32), ChunkSplit::Vertical)));
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(0)), Region::Room(RoomId::new(1)), Point::new(20f32, 8f32), Point::new(20f32, 12f32), DoorKind::Loop));
+        dungeon
+    }
+
+    #[test]
+    fn test_spacing_and_clearance() {
+        let dungeon = dungeon();
+        let spawn_options = SpawnOptions::new(3f32, 2f32, 4f32)
+            .with_density(SpawnDensity::new("monster", SpawnTarget::Room, 0.05f32));
+        let spawns = SpawnBuilder::new(&spawn_options).place(&dungeon, &mut thread_rng());
+        assert!(!spawns.is_empty());
+        for (idx, spawn) in spawns.iter().enumerate() {
+            let room = match spawn.region() {
+                Region::Room(room) => dungeon.room(room),
+                Region::Hallway(_) => panic!("no hallway densities were configured")
+            };
+            assert!(spawn.position().x() >= room.lower_left().x() + 2f32 && spawn.position().x() <= room.upper_right().x() - 2f32);
+            assert!(spawn.position().y() >= room.lower_left().y() + 2f32 && spawn.position().y() <= room.upper_right().y() - 2f32);
+            let door = (Point::new(20f32, 8f32), Point::new(20f32, 12f32));
+            assert!(segment::distance_to_segment(spawn.position(), &door) >= 4f32);
+            for other in &spawns[idx + 1..] {
+                if other.region() == spawn.region() {
+                    assert!(spawn.position().distance(other.position()) >= 3f32);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_role_density() {
+        let mut dungeon = dungeon();
+        dungeon.room_mut(RoomId::new(1)).add_role(RoomRole::Treasure);
+        let spawn_options = SpawnOptions::new(1f32, 1f32, 1f32)
+            .with_density(SpawnDensity::new("loot", SpawnTarget::Role(RoomRole::Treasure), 0.01f32));
+        let spawns = SpawnBuilder::new(&spawn_options).place(&dungeon, &mut thread_rng());
+        assert_eq!(4, spawns.len());
+        assert!(spawns.iter().all(|spawn| spawn.region() == Region::Room(RoomId::new(1)) && spawn.kind() == "loot"));
+    }
+
+    #[test]
+    fn test_polygon_area() {
+        let points = vec![Point::new(0f32, 0f32), Point::new(0f32, 4f32), Point::new(3f32, 4f32), Point::new(3f32, 0f32)];
+        assert_eq!(12f64, polygon_area(&points));
+    }
+}
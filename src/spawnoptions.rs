@@ -0,0 +1,49 @@
+use roomrole::RoomRole;
+use scalar::Scalar;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum SpawnTarget {
+    Room,
+    Role(RoomRole),
+    Hallway,
+}
+
+/// Number of `kind` spawns per unit of area in every region matching `target`.
+pub struct SpawnDensity {
+    pub kind: String,
+    pub target: SpawnTarget,
+    pub per_area: f32,
+}
+
+impl SpawnDensity {
+    pub fn new(kind: &str, target: SpawnTarget, per_area: f32) -> SpawnDensity {
+        SpawnDensity { 
+            kind: kind.to_string(), 
+            target, 
+            per_area 
+        }
+    }
+}
+
+pub struct SpawnOptions<T = f32> {
+    pub min_distance: T,
+    pub wall_margin: T,
+    pub door_clearance: T,
+    pub densities: Vec<SpawnDensity>,
+}
+
+impl<T: Scalar> SpawnOptions<T> {
+    pub fn new(min_distance: T, wall_margin: T, door_clearance: T) -> SpawnOptions<T> {
+        SpawnOptions { 
+            min_distance, 
+            wall_margin, 
+            door_clearance, 
+            densities: Vec::new() 
+        }
+    }
+
+    pub fn with_density(mut self, density: SpawnDensity) -> SpawnOptions<T> {
+        self.densities.push(density);
+        self
+    }
+}
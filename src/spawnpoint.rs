@@ -0,0 +1,28 @@
+use point::Point;
+use door::Region;
+use scalar::Scalar;
+
+pub struct SpawnPoint<T = f32> {
+    position: Point<T>,
+    kind: String,
+    region: Region
+}
+
+impl<T: Scalar> SpawnPoint<T> {
+    pub fn new(position: Point<T>, kind: String, region: Region) -> SpawnPoint<T> {
+        SpawnPoint { position, kind, region }
+    }
+
+    pub fn position(&self) -> &Point<T> {
+        &self.position
+    }
+
+    /// Designer defined type of the spawn, for example "monster" or "loot".
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+}
@@ -0,0 +1,112 @@
+use chunk::Chunk;
+use scalar::Scalar;
+use rand::Rng;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// The order `DungeonBuilder` takes chunks in to split them.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SplitOrder {
+    /// Always the chunk with the largest area.
+    LargestFirst,
+    /// Chunks in the order they were cut, so the tree grows a level at a time.
+    BreadthFirst,
+    /// The newest chunk, so each branch is split down to its rooms before the next.
+    DepthFirst,
+    /// Any waiting chunk, picked at random.
+    Random,
+}
+
+/// Chunks waiting to be split, handed out in a `SplitOrder`.
+pub struct ChunkQueue<T = f32> {
+    split_order: SplitOrder,
+    largest: BinaryHeap<Chunk<T>>,
+    queue: VecDeque<Chunk<T>>,
+    area: T
+}
+
+impl<T: Scalar> ChunkQueue<T> {
+    pub fn new(split_order: SplitOrder) -> ChunkQueue<T> {
+        ChunkQueue { split_order, largest: BinaryHeap::new(), queue: VecDeque::new(), area: T::ZERO }
+    }
+
+    pub fn push(&mut self, chunk: Chunk<T>) {
+        self.area += chunk.area();
+        match self.split_order {
+            SplitOrder::LargestFirst => self.largest.push(chunk),
+            _ => self.queue.push_back(chunk)
+        }
+    }
+
+    pub fn pop<R: Rng>(&mut self, rng: &mut R) -> Option<Chunk<T>> {
+        let chunk = match self.split_order {
+            SplitOrder::LargestFirst => self.largest.pop(),
+            SplitOrder::BreadthFirst => self.queue.pop_front(),
+            SplitOrder::DepthFirst => self.queue.pop_back(),
+            SplitOrder::Random if self.queue.is_empty() => None,
+            SplitOrder::Random => {
+                let idx = rng.gen_range(0, self.queue.len());
+                self.queue.swap_remove_back(idx)
+            }
+        };
+        if let Some(ref chunk) = chunk {
+            self.area -= chunk.area();
+        }
+        chunk
+    }
+
+    pub fn len(&self) -> usize {
+        self.largest.len() + self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Total area of the waiting chunks.
+    pub fn area(&self) -> T {
+        self.area
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use chunk::ChunkSplit;
+    use rand::{SeedableRng, Isaac64Rng};
+
+    fn queue(split_order: SplitOrder) -> ChunkQueue<i32> {
+        let mut queue = ChunkQueue::new(split_order);
+        for &width in &[2i32, 5i32, 3i32] {
+            queue.push(Chunk::new(Point::new(0i32, 0i32), Point::new(width, 1i32), ChunkSplit::Vertical));
+        }
+        queue
+    }
+
+    fn widths(mut queue: ChunkQueue<i32>) -> Vec<i32> {
+        let mut rng = Isaac64Rng::from_seed(&[1]);
+        let mut widths = Vec::new();
+        while let Some(chunk) = queue.pop(&mut rng) {
+            widths.push(chunk.width());
+        }
+        assert_eq!(0, queue.area());
+        widths
+    }
+
+    #[test]
+    fn test_orders() {
+        assert_eq!(vec![5, 3, 2], widths(queue(SplitOrder::LargestFirst)));
+        assert_eq!(vec![2, 5, 3], widths(queue(SplitOrder::BreadthFirst)));
+        assert_eq!(vec![3, 5, 2], widths(queue(SplitOrder::DepthFirst)));
+        let mut random = widths(queue(SplitOrder::Random));
+        random.sort();
+        assert_eq!(vec![2, 3, 5], random);
+    }
+
+    #[test]
+    fn test_area() {
+        let queue = queue(SplitOrder::BreadthFirst);
+        assert_eq!(3, queue.len());
+        assert_eq!(10, queue.area());
+    }
+}
@@ -0,0 +1,148 @@
+use adjacency::Orientation;
+use rect::Rect;
+use scalar::Scalar;
+use rand::Rng;
+use rand::distributions::{IndependentSample, Normal};
+
+const GOLDEN_RATIO: f64 = 0.618_033_988_749_895;
+
+/// Decides how `DungeonBuilder` cuts a chunk in two. The orientation is that of the cut,
+/// so a `Vertical` cut leaves a left and a right piece. Both methods only run on chunks
+/// that can be cut, and `position` gets the range that keeps both pieces within the
+/// dimension options.
+pub trait SplitStrategy<T: Scalar> {
+    /// Picks the cut direction given the direction of the cut that made `rect`. The
+    /// default alternates, falling back to the other direction when one is not allowed.
+    fn orientation(&self, rect: &Rect<T>, previous: Orientation, vertical: bool, horizontal: bool, rng: &mut dyn Rng) -> Orientation {
+        let _ = (rect, rng);
+        match previous {
+            Orientation::Horizontal if vertical => Orientation::Vertical,
+            Orientation::Horizontal => Orientation::Horizontal,
+            Orientation::Vertical if horizontal => Orientation::Horizontal,
+            Orientation::Vertical => Orientation::Vertical,
+        }
+    }
+
+    /// Picks where to cut, at least `min` and below `max`. The default is uniform.
+    fn position(&self, min: T, max: T, mut rng: &mut dyn Rng) -> T {
+        if min < max { Rng::gen_range(&mut rng, min, max) } else { min }
+    }
+}
+
+/// Alternates the cut direction and cuts anywhere, which gives the classic grid-like look.
+pub struct AlternatingSplit;
+
+impl<T: Scalar> SplitStrategy<T> for AlternatingSplit {
+}
+
+/// Always cuts across the longer side, keeping rooms close to square.
+pub struct LongestAxisSplit;
+
+impl<T: Scalar> SplitStrategy<T> for LongestAxisSplit {
+    fn orientation(&self, rect: &Rect<T>, _previous: Orientation, vertical: bool, horizontal: bool, _rng: &mut dyn Rng) -> Orientation {
+        if vertical && (rect.width() >= rect.height() || !horizontal) {
+            Orientation::Vertical
+        } else {
+            Orientation::Horizontal
+        }
+    }
+}
+
+/// Cuts near the golden section of the range, on either side, moved by up to `jitter`
+/// of the range.
+pub struct GoldenRatioSplit {
+    pub jitter: f64
+}
+
+impl GoldenRatioSplit {
+    pub fn new(jitter: f64) -> GoldenRatioSplit {
+        GoldenRatioSplit { jitter }
+    }
+}
+
+impl<T: Scalar> SplitStrategy<T> for GoldenRatioSplit {
+    fn position(&self, min: T, max: T, mut rng: &mut dyn Rng) -> T {
+        let ratio = if Rng::gen::<bool>(&mut rng) { GOLDEN_RATIO } else { 1f64 - GOLDEN_RATIO };
+        let offset = ratio + self.jitter * (Rng::gen::<f64>(&mut rng) * 2f64 - 1f64);
+        within(min, max, min.to_f64() + (max - min).to_f64() * offset)
+    }
+}
+
+/// Cuts around the center of the range, with `std_dev` given as a share of the range.
+pub struct NormalSplit {
+    pub std_dev: f64
+}
+
+impl NormalSplit {
+    pub fn new(std_dev: f64) -> NormalSplit {
+        NormalSplit { std_dev }
+    }
+}
+
+impl<T: Scalar> SplitStrategy<T> for NormalSplit {
+    fn position(&self, min: T, max: T, mut rng: &mut dyn Rng) -> T {
+        let range = (max - min).to_f64();
+        let normal = Normal::new(min.to_f64() + range / 2f64, range * self.std_dev);
+        within(min, max, normal.ind_sample(&mut rng))
+    }
+}
+
+/// Rounds `value` and clamps it to the cuts allowed between `min` and `max`.
+fn within<T: Scalar>(min: T, max: T, value: f64) -> T {
+    if min < max {
+        T::from_f64(value).max(min).min(max - T::ONE)
+    } else {
+        min
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use point::Point;
+    use rand::{SeedableRng, Isaac64Rng};
+
+    fn rect(width: f32, height: f32) -> Rect {
+        Rect::new(Point::new(0f32, 0f32), Point::new(width, height))
+    }
+
+    #[test]
+    fn test_alternating_orientation() {
+        let mut rng = Isaac64Rng::from_seed(&[1]);
+        let strategy = AlternatingSplit;
+        assert_eq!(Orientation::Vertical, strategy.orientation(&rect(10f32, 40f32), Orientation::Horizontal, true, true, &mut rng));
+        assert_eq!(Orientation::Horizontal, strategy.orientation(&rect(40f32, 10f32), Orientation::Vertical, true, true, &mut rng));
+        assert_eq!(Orientation::Horizontal, strategy.orientation(&rect(10f32, 40f32), Orientation::Horizontal, false, true, &mut rng));
+    }
+
+    #[test]
+    fn test_longest_axis_orientation() {
+        let mut rng = Isaac64Rng::from_seed(&[1]);
+        let strategy = LongestAxisSplit;
+        assert_eq!(Orientation::Horizontal, strategy.orientation(&rect(10f32, 40f32), Orientation::Vertical, true, true, &mut rng));
+        assert_eq!(Orientation::Vertical, strategy.orientation(&rect(40f32, 10f32), Orientation::Horizontal, true, true, &mut rng));
+        assert_eq!(Orientation::Horizontal, strategy.orientation(&rect(40f32, 10f32), Orientation::Horizontal, false, true, &mut rng));
+    }
+
+    #[test]
+    fn test_golden_ratio_position() {
+        let mut rng = Isaac64Rng::from_seed(&[2]);
+        for _ in 0..20 {
+            let at = GoldenRatioSplit::new(0f64).position(0i32, 100i32, &mut rng);
+            assert!(at == 38 || at == 62, "cut at {}", at);
+            let at = GoldenRatioSplit::new(0.1f64).position(0f32, 100f32, &mut rng);
+            assert!((28f32..=49f32).contains(&at) || (51f32..=72f32).contains(&at), "cut at {}", at);
+        }
+    }
+
+    #[test]
+    fn test_normal_position() {
+        let mut rng = Isaac64Rng::from_seed(&[3]);
+        let strategy = NormalSplit::new(0.15f64);
+        let cuts : Vec<f32> = (0..500).map(|_| strategy.position(10f32, 50f32, &mut rng)).collect();
+        assert!(cuts.iter().all(|&at| (10f32..=49f32).contains(&at)));
+        let mean = cuts.iter().sum::<f32>() / cuts.len() as f32;
+        assert!((mean - 30f32).abs() < 2f32, "mean cut at {}", mean);
+        assert_eq!(10f32, strategy.position(10f32, 10f32, &mut rng));
+    }
+}
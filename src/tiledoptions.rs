@@ -0,0 +1,23 @@
+use scalar::Scalar;
+
+/// How `Dungeon::to_tile_map` lays the dungeon out on a Tiled map.
+pub struct TiledOptions<T = f32> {
+    /// Dungeon units covered by one tile.
+    pub tile_size: T,
+    /// Size of a tile in pixels, which Tiled places objects by.
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Tileset image holding the floor, hallway and wall tiles in that order along a row.
+    pub tileset_image: String,
+}
+
+impl<T: Scalar> TiledOptions<T> {
+    pub fn new(tile_size: T) -> TiledOptions<T> {
+        TiledOptions {
+            tile_size,
+            tile_width: 16,
+            tile_height: 16,
+            tileset_image: "dungeon.png".to_string()
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use json;
+use std::fmt::Write;
+
+/// Tile ids of the layers `Dungeon::to_tile_map` fills, in the order of the tileset.
+/// Empty tiles are zero.
+pub const FLOOR_TILE: u32 = 1;
+pub const HALLWAY_TILE: u32 = 2;
+pub const WALL_TILE: u32 = 3;
+
+/// A custom property on a map object.
+#[derive(Clone, PartialEq, Debug)]
+pub enum PropertyValue {
+    Int(i64),
+    Bool(bool),
+    Text(String),
+}
+
+impl PropertyValue {
+    fn kind(&self) -> &'static str {
+        match *self {
+            PropertyValue::Int(_) => "int",
+            PropertyValue::Bool(_) => "bool",
+            PropertyValue::Text(_) => "string"
+        }
+    }
+
+    fn to_json(&self) -> String {
+        match *self {
+            PropertyValue::Text(ref text) => json::quote(text),
+            _ => self.to_text()
+        }
+    }
+
+    fn to_text(&self) -> String {
+        match *self {
+            PropertyValue::Int(value) => value.to_string(),
+            PropertyValue::Bool(value) => value.to_string(),
+            PropertyValue::Text(ref text) => text.clone()
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ObjectShape {
+    /// Width and height, reaching right and down from the object position.
+    Rectangle(f64, f64),
+    Point,
+    /// Points measured from the object position.
+    Polyline(Vec<(f64, f64)>),
+}
+
+/// An object on one of the object layers, in pixels from the top left of the map.
+pub struct TileObject {
+    id: usize,
+    name: String,
+    kind: String,
+    x: f64,
+    y: f64,
+    shape: ObjectShape,
+    properties: Vec<(String, PropertyValue)>
+}
+
+impl TileObject {
+    pub fn new(name: &str, kind: &str, x: f64, y: f64, shape: ObjectShape) -> TileObject {
+        TileObject { id: 0, name: name.to_string(), kind: kind.to_string(), x, y, shape, properties: Vec::new() }
+    }
+
+    pub fn with_property(mut self, name: &str, value: PropertyValue) -> TileObject {
+        self.properties.push((name.to_string(), value));
+        self
+    }
+
+    /// The id the map gave the object, counting from one.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// What Tiled calls the object's type or class.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn position(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+
+    pub fn shape(&self) -> &ObjectShape {
+        &self.shape
+    }
+
+    pub fn properties(&self) -> &[(String, PropertyValue)] {
+        &self.properties[..]
+    }
+
+    fn to_json(&self) -> String {
+        let mut json = format!("{{\"id\":{},\"name\":{},\"type\":{},\"x\":{},\"y\":{},\"rotation\":0,\"visible\":true",
+            self.id, json::quote(&self.name), json::quote(&self.kind), self.x, self.y);
+        match self.shape {
+            ObjectShape::Rectangle(width, height) => write!(json, ",\"width\":{},\"height\":{}", width, height).unwrap(),
+            ObjectShape::Point => json.push_str(",\"width\":0,\"height\":0,\"point\":true"),
+            ObjectShape::Polyline(ref points) => {
+                let points : Vec<String> = points.iter().map(|&(x, y)| format!("{{\"x\":{},\"y\":{}}}", x, y)).collect();
+                write!(json, ",\"width\":0,\"height\":0,\"polyline\":[{}]", points.join(",")).unwrap();
+            }
+        }
+        if !self.properties.is_empty() {
+            let properties : Vec<String> = self.properties.iter()
+                .map(|(name, value)| format!("{{\"name\":{},\"type\":\"{}\",\"value\":{}}}", json::quote(name), value.kind(), value.to_json()))
+                .collect();
+            write!(json, ",\"properties\":[{}]", properties.join(",")).unwrap();
+        }
+        json.push('}');
+        json
+    }
+
+    fn to_tmx(&self) -> String {
+        let mut tmx = format!("  <object id=\"{}\" name=\"{}\" type=\"{}\" x=\"{}\" y=\"{}\"", self.id, xml_string(&self.name), xml_string(&self.kind), self.x, self.y);
+        if let ObjectShape::Rectangle(width, height) = self.shape {
+            write!(tmx, " width=\"{}\" height=\"{}\"", width, height).unwrap();
+        }
+        tmx.push_str(">\n");
+        if !self.properties.is_empty() {
+            tmx.push_str("   <properties>\n");
+            for (name, value) in &self.properties {
+                writeln!(tmx, "    <property name=\"{}\" type=\"{}\" value=\"{}\"/>", xml_string(name), value.kind(), xml_string(&value.to_text())).unwrap();
+            }
+            tmx.push_str("   </properties>\n");
+        }
+        match self.shape {
+            ObjectShape::Point => tmx.push_str("   <point/>\n"),
+            ObjectShape::Polyline(ref points) => {
+                let points : Vec<String> = points.iter().map(|&(x, y)| format!("{},{}", x, y)).collect();
+                writeln!(tmx, "   <polyline points=\"{}\"/>", points.join(" ")).unwrap();
+            },
+            ObjectShape::Rectangle(..) => {}
+        }
+        tmx.push_str("  </object>\n");
+        tmx
+    }
+}
+
+pub struct TileLayer {
+    name: String,
+    tiles: Vec<u32>
+}
+
+impl TileLayer {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Tile ids row by row, starting at the top left.
+    pub fn tiles(&self) -> &[u32] {
+        &self.tiles[..]
+    }
+}
+
+pub struct ObjectGroup {
+    name: String,
+    objects: Vec<TileObject>
+}
+
+impl ObjectGroup {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn objects(&self) -> &[TileObject] {
+        &self.objects[..]
+    }
+}
+
+/// A map for the Tiled editor: tile layers drawn from a three tile tileset, with object
+/// layers above them.
+pub struct TileMap {
+    columns: usize,
+    rows: usize,
+    tile_width: u32,
+    tile_height: u32,
+    tileset_image: String,
+    layers: Vec<TileLayer>,
+    object_groups: Vec<ObjectGroup>,
+    objects: usize
+}
+
+impl TileMap {
+    pub fn new(columns: usize, rows: usize, tile_width: u32, tile_height: u32, tileset_image: &str) -> TileMap {
+        TileMap { columns, rows, tile_width, tile_height, tileset_image: tileset_image.to_string(), layers: Vec::new(), object_groups: Vec::new(), objects: 0 }
+    }
+
+    /// Adds a tile layer above those added before. `tiles` holds a tile id for every
+    /// cell, row by row from the top left.
+    pub fn add_layer(&mut self, name: &str, tiles: Vec<u32>) {
+        assert_eq!(self.columns * self.rows, tiles.len(), "layer {} does not cover the map", name);
+        self.layers.push(TileLayer { name: name.to_string(), tiles });
+    }
+
+    /// Adds an object to the named object layer, creating the layer if there is none yet,
+    /// and returns the id it was given.
+    pub fn add_object(&mut self, group: &str, mut object: TileObject) -> usize {
+        self.objects += 1;
+        object.id = self.objects;
+        match self.object_groups.iter().position(|object_group| object_group.name == group) {
+            Some(idx) => self.object_groups[idx].objects.push(object),
+            None => self.object_groups.push(ObjectGroup { name: group.to_string(), objects: vec![object] })
+        }
+        self.objects
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn layers(&self) -> &[TileLayer] {
+        &self.layers[..]
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&TileLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    pub fn object_groups(&self) -> &[ObjectGroup] {
+        &self.object_groups[..]
+    }
+
+    pub fn object_group(&self, name: &str) -> Option<&ObjectGroup> {
+        self.object_groups.iter().find(|object_group| object_group.name == name)
+    }
+
+    /// Writes the map in Tiled's JSON format, with the tileset embedded.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"type\":\"map\",\"version\":\"1.10\",\"tiledversion\":\"1.10.2\",\"orientation\":\"orthogonal\",\"renderorder\":\"right-down\",\"infinite\":false,\"compressionlevel\":-1");
+        write!(json, ",\"width\":{},\"height\":{},\"tilewidth\":{},\"tileheight\":{},\"nextlayerid\":{},\"nextobjectid\":{}",
+            self.columns, self.rows, self.tile_width, self.tile_height, self.layers.len() + self.object_groups.len() + 1, self.objects + 1).unwrap();
+        write!(json, ",\"tilesets\":[{{\"firstgid\":1,\"name\":\"dungeon\",\"tilewidth\":{},\"tileheight\":{},\"tilecount\":3,\"columns\":3,\"margin\":0,\"spacing\":0,\"image\":{},\"imagewidth\":{},\"imageheight\":{}}}]",
+            self.tile_width, self.tile_height, json::quote(&self.tileset_image), self.tile_width * 3, self.tile_height).unwrap();
+        let mut layers = Vec::new();
+        for (idx, layer) in self.layers.iter().enumerate() {
+            let tiles : Vec<String> = layer.tiles.iter().map(|tile| tile.to_string()).collect();
+            layers.push(format!("{{\"id\":{},\"name\":{},\"type\":\"tilelayer\",\"x\":0,\"y\":0,\"width\":{},\"height\":{},\"opacity\":1,\"visible\":true,\"data\":[{}]}}",
+                idx + 1, json::quote(&layer.name), self.columns, self.rows, tiles.join(",")));
+        }
+        for (idx, object_group) in self.object_groups.iter().enumerate() {
+            let objects : Vec<String> = object_group.objects.iter().map(|object| object.to_json()).collect();
+            layers.push(format!("{{\"id\":{},\"name\":{},\"type\":\"objectgroup\",\"draworder\":\"topdown\",\"x\":0,\"y\":0,\"opacity\":1,\"visible\":true,\"objects\":[{}]}}",
+                self.layers.len() + idx + 1, json::quote(&object_group.name), objects.join(",")));
+        }
+        write!(json, ",\"layers\":[{}]}}", layers.join(",")).unwrap();
+        json
+    }
+
+    /// Writes the map as a Tiled TMX file, with the tileset embedded and the tile
+    /// layers as CSV.
+    pub fn to_tmx(&self) -> String {
+        let mut tmx = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        writeln!(tmx, "<map version=\"1.10\" tiledversion=\"1.10.2\" orientation=\"orthogonal\" renderorder=\"right-down\" width=\"{}\" height=\"{}\" tilewidth=\"{}\" tileheight=\"{}\" infinite=\"0\" nextlayerid=\"{}\" nextobjectid=\"{}\">",
+            self.columns, self.rows, self.tile_width, self.tile_height, self.layers.len() + self.object_groups.len() + 1, self.objects + 1).unwrap();
+        writeln!(tmx, " <tileset firstgid=\"1\" name=\"dungeon\" tilewidth=\"{}\" tileheight=\"{}\" tilecount=\"3\" columns=\"3\">", self.tile_width, self.tile_height).unwrap();
+        writeln!(tmx, "  <image source=\"{}\" width=\"{}\" height=\"{}\"/>\n </tileset>", xml_string(&self.tileset_image), self.tile_width * 3, self.tile_height).unwrap();
+        for (idx, layer) in self.layers.iter().enumerate() {
+            writeln!(tmx, " <layer id=\"{}\" name=\"{}\" width=\"{}\" height=\"{}\">\n  <data encoding=\"csv\">", idx + 1, xml_string(&layer.name), self.columns, self.rows).unwrap();
+            let rows : Vec<String> = layer.tiles.chunks(self.columns.max(1)).map(|row| {
+                row.iter().map(|tile| tile.to_string()).collect::<Vec<String>>().join(",")
+            }).collect();
+            writeln!(tmx, "{}\n</data>\n </layer>", rows.join(",\n")).unwrap();
+        }
+        for (idx, object_group) in self.object_groups.iter().enumerate() {
+            writeln!(tmx, " <objectgroup id=\"{}\" name=\"{}\">", self.layers.len() + idx + 1, xml_string(&object_group.name)).unwrap();
+            for object in &object_group.objects {
+                tmx.push_str(&object.to_tmx());
+            }
+            tmx.push_str(" </objectgroup>\n");
+        }
+        tmx.push_str("</map>\n");
+        tmx
+    }
+}
+
+fn xml_string(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile_map() -> TileMap {
+        let mut tile_map = TileMap::new(2, 2, 16, 16, "tiles.png");
+        tile_map.add_layer("floor", vec![FLOOR_TILE, 0, HALLWAY_TILE, WALL_TILE]);
+        tile_map.add_object("rooms", TileObject::new("room 0", "room", 0f64, 16f64, ObjectShape::Rectangle(16f64, 8f64))
+            .with_property("roles", PropertyValue::Text("boss \"lair\"".to_string()))
+            .with_property("bsp_depth", PropertyValue::Int(2)));
+        tile_map.add_object("spawns", TileObject::new("", "loot", 4f64, 4.5f64, ObjectShape::Point));
+        tile_map
+    }
+
+    #[test]
+    fn test_json() {
+        let json = tile_map().to_json();
+        assert!(json.contains("\"width\":2,\"height\":2,\"tilewidth\":16,\"tileheight\":16,\"nextlayerid\":4,\"nextobjectid\":3"));
+        assert!(json.contains("\"image\":\"tiles.png\",\"imagewidth\":48,\"imageheight\":16"));
+        assert!(json.contains("{\"id\":1,\"name\":\"floor\",\"type\":\"tilelayer\",\"x\":0,\"y\":0,\"width\":2,\"height\":2,\"opacity\":1,\"visible\":true,\"data\":[1,0,2,3]}"));
+        assert!(json.contains("{\"id\":1,\"name\":\"room 0\",\"type\":\"room\",\"x\":0,\"y\":16,\"rotation\":0,\"visible\":true,\"width\":16,\"height\":8,\"properties\":[{\"name\":\"roles\",\"type\":\"string\",\"value\":\"boss \\\"lair\\\"\"},{\"name\":\"bsp_depth\",\"type\":\"int\",\"value\":2}]}"));
+        assert!(json.contains("{\"id\":3,\"name\":\"spawns\",\"type\":\"objectgroup\""));
+        assert!(json.contains("\"x\":4,\"y\":4.5,\"rotation\":0,\"visible\":true,\"width\":0,\"height\":0,\"point\":true}"));
+    }
+
+    #[test]
+    fn test_tmx() {
+        let tmx = tile_map().to_tmx();
+        assert!(tmx.contains("<data encoding=\"csv\">\n1,0,\n2,3\n</data>"));
+        assert!(tmx.contains("<property name=\"roles\" type=\"string\" value=\"boss &quot;lair&quot;\"/>"));
+        assert!(tmx.contains("<object id=\"2\" name=\"\" type=\"loot\" x=\"4\" y=\"4.5\">\n   <point/>\n  </object>"));
+    }
+}
@@ -0,0 +1,199 @@
+use dungeon::Dungeon;
+use tilemap::{TileMap, TileObject, ObjectShape, PropertyValue, FLOOR_TILE, HALLWAY_TILE, WALL_TILE};
+use tiledoptions::TiledOptions;
+use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+use segment;
+
+/// A hallway outline in map coordinates.
+struct Outline {
+    bounds: Rect<f64>,
+    points: Vec<Point<f64>>,
+    holes: Vec<Vec<Point<f64>>>
+}
+
+impl Outline {
+    fn contains(&self, point: &Point<f64>) -> bool {
+        self.bounds.contains(point) && segment::polygon_contains(&self.points, point) &&
+            !self.holes.iter().any(|hole| segment::polygon_contains(hole, point))
+    }
+}
+
+pub struct TileMapBuilder<'a, T: 'a> {
+    tiled_options: &'a TiledOptions<T>
+}
+
+impl<'a, T: Scalar> TileMapBuilder<'a, T> {
+    pub fn new(tiled_options: &'a TiledOptions<T>) -> TileMapBuilder<'a, T> {
+        TileMapBuilder { tiled_options }
+    }
+
+    /// Marks every tile whose middle lies in a room, hallway or wall, on a grid one tile
+    /// larger than the dungeon all round. Without walls from `build_walls`, the empty
+    /// tiles bordering the floor become walls instead.
+    pub fn tile_map(&self, dungeon: &Dungeon<T>) -> TileMap {
+        let options = self.tiled_options;
+        let size = options.tile_size.to_f64();
+        let bounds = bounds(dungeon);
+        let (columns, rows) = match bounds {
+            Some(ref bounds) => (tiles(bounds.width(), size), tiles(bounds.height(), size)),
+            None => (0, 0)
+        };
+        let (left, top) = bounds.map_or((0f64, 0f64), |bounds| (bounds.lower_left().x() - size, bounds.upper_right().y() + size));
+        let mut tile_map = TileMap::new(columns, rows, options.tile_width, options.tile_height, &options.tileset_image);
+
+        let middles : Vec<Point<f64>> = (0..rows * columns)
+            .map(|idx| Point::new(left + ((idx % columns) as f64 + 0.5f64) * size, top - ((idx / columns) as f64 + 0.5f64) * size))
+            .collect();
+        let rooms : Vec<Rect<f64>> = dungeon.rooms().iter().map(|room| cast(room.rect())).collect();
+        let floor : Vec<u32> = middles.iter()
+            .map(|middle| if rooms.iter().any(|rect| rect.contains(middle)) { FLOOR_TILE } else { 0 })
+            .collect();
+        let hallways : Vec<Outline> = dungeon.hallways().iter().map(|hallway| {
+            let points = points(hallway.points());
+            Outline { bounds: outline_bounds(&points), points, holes: hallway.holes().iter().map(|hole| self::points(hole)).collect() }
+        }).collect();
+        let hallway_tiles : Vec<u32> = middles.iter().enumerate().map(|(idx, middle)| {
+            let inside = floor[idx] == 0 && hallways.iter().any(|hallway| hallway.contains(middle));
+            if inside { HALLWAY_TILE } else { 0 }
+        }).collect();
+        let walls : Vec<u32> = if dungeon.walls().is_empty() {
+            (0..middles.len()).map(|idx| {
+                let (column, row) = ((idx % columns) as isize, (idx / columns) as isize);
+                let open = |column: isize, row: isize| {
+                    column >= 0 && row >= 0 && (column as usize) < columns && (row as usize) < rows &&
+                        (floor[row as usize * columns + column as usize] != 0 || hallway_tiles[row as usize * columns + column as usize] != 0)
+                };
+                let borders = (-1..2).any(|dy| (-1..2).any(|dx| open(column + dx, row + dy)));
+                if !open(column, row) && borders { WALL_TILE } else { 0 }
+            }).collect()
+        } else {
+            let walls : Vec<Vec<Point<f64>>> = dungeon.walls().iter().map(|wall| points(wall.points())).collect();
+            middles.iter().map(|middle| if walls.iter().any(|wall| segment::polygon_contains(wall, middle)) { WALL_TILE } else { 0 }).collect()
+        };
+        tile_map.add_layer("floor", floor);
+        tile_map.add_layer("hallways", hallway_tiles);
+        tile_map.add_layer("walls", walls);
+
+        let pixels = |point: &Point<f64>| {
+            ((point.x() - left) / size * options.tile_width as f64, (top - point.y()) / size * options.tile_height as f64)
+        };
+        for id in dungeon.room_ids() {
+            let room = dungeon.room(id);
+            let rect = cast(room.rect());
+            let (x, y) = pixels(&Point::new(rect.lower_left().x(), rect.upper_right().y()));
+            let (right, bottom) = pixels(&Point::new(rect.upper_right().x(), rect.lower_left().y()));
+            let roles : Vec<&str> = room.roles().iter().map(|role| role.name()).collect();
+            let mut object = TileObject::new(&id.to_string(), "room", x, y, ObjectShape::Rectangle(right - x, bottom - y))
+                .with_property("id", PropertyValue::Int(id.index() as i64))
+                .with_property("bsp_depth", PropertyValue::Int(room.bsp_depth() as i64))
+                .with_property("roles", PropertyValue::Text(roles.join(",")))
+                .with_property("fixed", PropertyValue::Bool(dungeon.fixed_rooms().contains(&id)));
+            if let Some(placement) = dungeon.prefab_in(id) {
+                object = object.with_property("prefab", PropertyValue::Text(placement.name().to_string()));
+            }
+            tile_map.add_object("rooms", object);
+        }
+        for (idx, door) in dungeon.doors().iter().enumerate() {
+            let (x, y) = pixels(&door.start().cast::<f64>());
+            let (end_x, end_y) = pixels(&door.end().cast::<f64>());
+            tile_map.add_object("doors", TileObject::new(&format!("door {}", idx), "door", x, y, ObjectShape::Polyline(vec![(0f64, 0f64), (end_x - x, end_y - y)]))
+                .with_property("from", PropertyValue::Text(door.from().to_string()))
+                .with_property("to", PropertyValue::Text(door.to().to_string()))
+                .with_property("kind", PropertyValue::Text(door.kind().name().to_string())));
+        }
+        for spawn_point in dungeon.spawn_points() {
+            let (x, y) = pixels(&spawn_point.position().cast::<f64>());
+            tile_map.add_object("spawns", TileObject::new("", spawn_point.kind(), x, y, ObjectShape::Point)
+                .with_property("region", PropertyValue::Text(spawn_point.region().to_string())));
+        }
+        tile_map
+    }
+}
+
+/// Tiles needed to cover `length`, plus one on either side.
+fn tiles<T: Scalar>(length: T, size: f64) -> usize {
+    (length.to_f64() / size - 1e-9).ceil().max(0f64) as usize + 2
+}
+
+fn cast<T: Scalar>(rect: &Rect<T>) -> Rect<f64> {
+    Rect::new(rect.lower_left().cast::<f64>(), rect.upper_right().cast::<f64>())
+}
+
+fn points<T: Scalar>(points: &[Point<T>]) -> Vec<Point<f64>> {
+    points.iter().map(|point| point.cast::<f64>()).collect()
+}
+
+fn outline_bounds(points: &[Point<f64>]) -> Rect<f64> {
+    points.iter().fold(Rect::new(points[0], points[0]), |bounds, point| bounds.union(&Rect::new(*point, *point)))
+}
+
+/// Everything drawn on the map: rooms, hallways and walls.
+fn bounds<T: Scalar>(dungeon: &Dungeon<T>) -> Option<Rect<f64>> {
+    let rects = dungeon.rooms().iter().map(|room| cast(room.rect()))
+        .chain(dungeon.hallways().iter().map(|hallway| outline_bounds(&points(hallway.points()))))
+        .chain(dungeon.walls().iter().map(|wall| cast(&wall.bounds())));
+    rects.fold(None, |bounds : Option<Rect<f64>>, rect| Some(bounds.map_or(rect, |bounds| bounds.union(&rect))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use room::{Room, RoomId};
+    use hallway::Hallway;
+    use chunk::{Chunk, ChunkSplit};
+    use door::{Door, DoorKind, Region};
+    use roomrole::RoomRole;
+    use walloptions::WallOptions;
+
+    fn dungeon() -> Dungeon<i32> {
+        let mut dungeon = Dungeon::new();
+        let room = dungeon.add_room(Room::new(Chunk::new(Point::new(0i32, 0i32), Point::new(4i32, 4i32), ChunkSplit::Vertical)));
+        dungeon.room_mut(room).add_role(RoomRole::Boss);
+        dungeon.add_hallways(vec![Hallway::new(vec![Point::new(4i32, 1i32), Point::new(4i32, 3i32), Point::new(8i32, 3i32), Point::new(8i32, 1i32)])]);
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(0)), Region::Hallway(0), Point::new(4i32, 1i32), Point::new(4i32, 3i32), DoorKind::Hallway));
+        dungeon
+    }
+
+    fn rows(tile_map: &TileMap, layer: &str) -> Vec<String> {
+        tile_map.layer(layer).unwrap().tiles().chunks(tile_map.columns())
+            .map(|row| row.iter().map(|&tile| if tile == 0 { '.' } else { '#' }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_layers() {
+        let tile_map = TileMapBuilder::new(&TiledOptions::new(1i32)).tile_map(&dungeon());
+        assert_eq!((10, 6), (tile_map.columns(), tile_map.rows()));
+        assert_eq!(vec!["..........", ".####.....", ".####.....", ".####.....", ".####.....", ".........."], rows(&tile_map, "floor"));
+        assert_eq!(vec!["..........", "..........", ".....####.", ".....####.", "..........", ".........."], rows(&tile_map, "hallways"));
+        assert_eq!(vec!["######....", "#....#####", "#........#", "#........#", "#....#####", "######...."], rows(&tile_map, "walls"));
+    }
+
+    #[test]
+    fn test_built_walls() {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0i32, 0i32), Point::new(4i32, 4i32), ChunkSplit::Vertical)));
+        dungeon.add_hallways(vec![Hallway::new(vec![Point::new(4i32, 0i32), Point::new(4i32, 4i32), Point::new(8i32, 4i32), Point::new(8i32, 0i32)])]);
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(0)), Region::Hallway(0), Point::new(4i32, 1i32), Point::new(4i32, 3i32), DoorKind::Hallway));
+        dungeon.build_walls(&WallOptions::new(2i32));
+        let tile_map = TileMapBuilder::new(&TiledOptions::new(1i32)).tile_map(&dungeon);
+        assert_eq!((12, 8), (tile_map.columns(), tile_map.rows()));
+        assert_eq!(vec!["............", ".##########.", ".##########.", ".##......##.", ".##......##.", ".##########.", ".##########.", "............"],
+            rows(&tile_map, "walls"));
+    }
+
+    #[test]
+    fn test_objects() {
+        let tile_map = TileMapBuilder::new(&TiledOptions::new(1i32)).tile_map(&dungeon());
+        let room = &tile_map.object_group("rooms").unwrap().objects()[0];
+        assert_eq!((16f64, 16f64), room.position());
+        assert_eq!(ObjectShape::Rectangle(64f64, 64f64), *room.shape());
+        assert!(room.properties().contains(&("roles".to_string(), PropertyValue::Text("boss".to_string()))));
+        let door = &tile_map.object_group("doors").unwrap().objects()[0];
+        assert_eq!((80f64, 64f64), door.position());
+        assert_eq!(ObjectShape::Polyline(vec![(0f64, 0f64), (0f64, -32f64)]), *door.shape());
+        assert_eq!(2, door.id());
+    }
+}
@@ -0,0 +1,184 @@
+use point::Point;
+use segment;
+
+const EPSILON: f64 = 1e-9;
+
+/// Cuts a polygon into triangles by ear clipping, after joining each hole to the outline
+/// with a bridge to the nearest corner it can see. Indices count through `outline` and
+/// then through each of the `holes` in turn, and every triangle is counterclockwise.
+pub fn triangulate(outline: &[Point<f64>], holes: &[Vec<Point<f64>>]) -> Vec<[usize; 3]> {
+    let mut points = outline.to_vec();
+    let mut ring = oriented(0, outline, true);
+    let mut hole_rings = Vec::new();
+    for hole in holes {
+        if hole.len() >= 3 {
+            hole_rings.push(oriented(points.len(), hole, false));
+        }
+        points.extend(hole.iter().cloned());
+    }
+    hole_rings.sort_by(|a, b| {
+        let (a, b) = (points[a[rightmost(&points, a)]].x(), points[b[rightmost(&points, b)]].x());
+        b.partial_cmp(&a).expect("polygon coordinate is NaN")
+    });
+    for (idx, hole) in hole_rings.iter().enumerate() {
+        let start = rightmost(&points, hole);
+        if let Some(at) = bridge(&points, &ring, hole, start, &hole_rings[idx + 1..]) {
+            let mut joined = ring[..=at].to_vec();
+            joined.extend(hole[start..].iter().chain(hole[..=start].iter()));
+            joined.push(ring[at]);
+            joined.extend_from_slice(&ring[at + 1..]);
+            ring = joined;
+        }
+    }
+    clip(&points, ring)
+}
+
+/// Twice the area of the polygon, positive when its points run counterclockwise.
+pub fn signed_area(points: &[Point<f64>]) -> f64 {
+    segment::polygon_edges(points).iter().map(|&(start, end)| start.x() * end.y() - end.x() * start.y()).sum()
+}
+
+fn oriented(offset: usize, points: &[Point<f64>], counterclockwise: bool) -> Vec<usize> {
+    let mut ring : Vec<usize> = (offset..offset + points.len()).collect();
+    if (signed_area(points) > 0f64) != counterclockwise {
+        ring.reverse();
+    }
+    ring
+}
+
+fn rightmost(points: &[Point<f64>], ring: &[usize]) -> usize {
+    (0..ring.len()).fold(0, |best, at| if points[ring[at]].x() > points[ring[best]].x() { at } else { best })
+}
+
+/// How far `c` lies to the left of the line from `a` through `b`.
+fn turn(a: &Point<f64>, b: &Point<f64>, c: &Point<f64>) -> f64 {
+    (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())
+}
+
+fn same(a: &Point<f64>, b: &Point<f64>) -> bool {
+    (a.x() - b.x()).abs() <= EPSILON && (a.y() - b.y()).abs() <= EPSILON
+}
+
+/// Whether `point` lies inside the corner a ring makes at `at`, which is on the left of
+/// the ring whichever way it runs.
+fn in_corner(points: &[Point<f64>], ring: &[usize], at: usize, point: &Point<f64>) -> bool {
+    let count = ring.len();
+    let (before, corner, after) = (&points[ring[(at + count - 1) % count]], &points[ring[at]], &points[ring[(at + 1) % count]]);
+    let (left_of_before, left_of_after) = (turn(before, corner, point) > EPSILON, turn(corner, after, point) > EPSILON);
+    if turn(before, corner, after) >= 0f64 {
+        left_of_before && left_of_after
+    } else {
+        left_of_before || left_of_after
+    }
+}
+
+/// The place in `ring` closest to the corner `start` of `hole` that a straight bridge
+/// can reach without crossing any edge.
+fn bridge(points: &[Point<f64>], ring: &[usize], hole: &[usize], start: usize, others: &[Vec<usize>]) -> Option<usize> {
+    let from = points[hole[start]];
+    let mut order : Vec<usize> = (0..ring.len()).collect();
+    let distance = |at: &usize| {
+        let to = &points[ring[*at]];
+        (to.x() - from.x()).powi(2) + (to.y() - from.y()).powi(2)
+    };
+    order.sort_by(|a, b| distance(a).partial_cmp(&distance(b)).expect("polygon coordinate is NaN"));
+    order.into_iter().find(|&at| {
+        let to = points[ring[at]];
+        in_corner(points, ring, at, &from) && in_corner(points, hole, start, &to) &&
+            !Some(ring).into_iter().chain(Some(hole)).chain(others.iter().map(|other| &other[..])).any(|edges| {
+                (0..edges.len()).any(|idx| {
+                    let (a, b) = (points[edges[idx]], points[edges[(idx + 1) % edges.len()]]);
+                    let touches_end = same(&a, &from) || same(&b, &from) || same(&a, &to) || same(&b, &to);
+                    !touches_end && segment::segments_touch(&(from, to), &(a, b))
+                })
+            })
+    })
+}
+
+fn clip(points: &[Point<f64>], mut ring: Vec<usize>) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::with_capacity(ring.len());
+    while ring.len() > 3 {
+        let count = ring.len();
+        let corner = |at: usize| (ring[(at + count - 1) % count], ring[at], ring[(at + 1) % count]);
+        let ear = (0..count).find(|&at| {
+            let (a, b, c) = corner(at);
+            let bend = turn(&points[a], &points[b], &points[c]);
+            bend.abs() <= EPSILON || (bend > 0f64 && is_ear(points, &ring, a, b, c))
+        });
+        // Rounding can leave no clean ear, so fall back on the sharpest convex corner.
+        let at = match ear.or_else(|| (0..count).find(|&at| {
+            let (a, b, c) = corner(at);
+            turn(&points[a], &points[b], &points[c]) > 0f64
+        })) {
+            Some(at) => at,
+            None => break
+        };
+        let (a, b, c) = corner(at);
+        if turn(&points[a], &points[b], &points[c]) > EPSILON {
+            triangles.push([a, b, c]);
+        }
+        ring.remove(at);
+    }
+    if ring.len() == 3 && turn(&points[ring[0]], &points[ring[1]], &points[ring[2]]) > EPSILON {
+        triangles.push([ring[0], ring[1], ring[2]]);
+    }
+    triangles
+}
+
+/// True when no other corner of the ring lies in or on the triangle.
+fn is_ear(points: &[Point<f64>], ring: &[usize], a: usize, b: usize, c: usize) -> bool {
+    let (a, b, c) = (&points[a], &points[b], &points[c]);
+    !ring.iter().map(|&idx| &points[idx]).any(|point| {
+        !same(point, a) && !same(point, b) && !same(point, c) &&
+            turn(a, b, point) >= -EPSILON && turn(b, c, point) >= -EPSILON && turn(c, a, point) >= -EPSILON
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coordinates: &[(f64, f64)]) -> Vec<Point<f64>> {
+        coordinates.iter().map(|&(x, y)| Point::new(x, y)).collect()
+    }
+
+    fn area(points: &[Point<f64>], triangles: &[[usize; 3]]) -> f64 {
+        triangles.iter().map(|triangle| {
+            let area = turn(&points[triangle[0]], &points[triangle[1]], &points[triangle[2]]);
+            assert!(area > 0f64);
+            area / 2f64
+        }).sum()
+    }
+
+    #[test]
+    fn test_clockwise_square() {
+        let square = points(&[(0f64, 0f64), (0f64, 4f64), (4f64, 4f64), (4f64, 0f64)]);
+        let triangles = triangulate(&square, &[]);
+        assert_eq!(2, triangles.len());
+        assert_eq!(16f64, area(&square, &triangles));
+    }
+
+    #[test]
+    fn test_collinear_corners() {
+        let outline = points(&[(0f64, 0f64), (5f64, 0f64), (10f64, 0f64), (10f64, 5f64), (5f64, 5f64), (5f64, 10f64), (0f64, 10f64), (0f64, 5f64)]);
+        assert_eq!(75f64, area(&outline, &triangulate(&outline, &[])));
+    }
+
+    #[test]
+    fn test_holes() {
+        let outline = points(&[(0f64, 0f64), (20f64, 0f64), (20f64, 10f64), (0f64, 10f64)]);
+        let holes = vec![
+            points(&[(2f64, 2f64), (6f64, 2f64), (6f64, 8f64), (2f64, 8f64)]),
+            points(&[(10f64, 4f64), (10f64, 6f64), (16f64, 6f64), (16f64, 4f64)]),
+        ];
+        let triangles = triangulate(&outline, &holes);
+        let all : Vec<Point<f64>> = outline.iter().chain(holes.iter().flat_map(|hole| hole.iter())).cloned().collect();
+        assert_eq!(200f64 - 24f64 - 12f64, area(&all, &triangles));
+        for triangle in &triangles {
+            let middle = Point::new(
+                (all[triangle[0]].x() + all[triangle[1]].x() + all[triangle[2]].x()) / 3f64,
+                (all[triangle[0]].y() + all[triangle[1]].y() + all[triangle[2]].y()) / 3f64);
+            assert!(!holes.iter().any(|hole| segment::polygon_contains(hole, &middle)));
+        }
+    }
+}
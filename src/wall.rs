@@ -0,0 +1,43 @@
+use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WallKind {
+    /// Between two rooms or hallways.
+    Interior,
+    /// Between a room or hallway and the outside.
+    Exterior,
+}
+
+/// A solid piece of wall, outlined as a convex polygon. Walls along axis aligned
+/// boundaries are rectangles and never overlap each other.
+pub struct Wall<T = f32> {
+    points: Vec<Point<T>>,
+    kind: WallKind
+}
+
+impl<T: Scalar> Wall<T> {
+    pub fn new(points: Vec<Point<T>>, kind: WallKind) -> Wall<T> {
+        Wall { points, kind }
+    }
+
+    pub fn from_rect(rect: &Rect<T>, kind: WallKind) -> Wall<T> {
+        Wall { points: rect.corners().to_vec(), kind }
+    }
+
+    /// The outline, listed clockwise for rectangular walls.
+    pub fn points(&self) -> &[Point<T>] {
+        &self.points[..]
+    }
+
+    pub fn kind(&self) -> WallKind {
+        self.kind
+    }
+
+    /// The smallest rectangle around the wall, which is the wall itself unless it runs
+    /// diagonally.
+    pub fn bounds(&self) -> Rect<T> {
+        self.points.iter().fold(Rect::new(self.points[0], self.points[0]), |bounds, point| bounds.union(&Rect::new(*point, *point)))
+    }
+}
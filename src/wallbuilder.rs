@@ -0,0 +1,220 @@
+use dungeon::Dungeon;
+use wall::{Wall, WallKind};
+use walloptions::WallOptions;
+use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+
+/// The boundary edges and door openings lying on one axis aligned line.
+struct Line<T> {
+    vertical: bool,
+    at: T,
+    edges: Vec<(T, T)>,
+    doors: Vec<(T, T)>
+}
+
+/// A stretch of wall along a line. An end is extended over the corner when the boundary
+/// turns there, and left flush when the line goes on as another kind of wall or as a door.
+struct Piece<T> {
+    low: T,
+    high: T,
+    kind: WallKind,
+    extend_low: bool,
+    extend_high: bool
+}
+
+pub struct WallBuilder<'a, T: 'a> {
+    wall_options: &'a WallOptions<T>
+}
+
+impl<'a, T: Scalar> WallBuilder<'a, T> {
+    pub fn new(wall_options: &'a WallOptions<T>) -> WallBuilder<'a, T> {
+        WallBuilder { wall_options }
+    }
+
+    /// Walls along every boundary of the rooms and hallways, with the doors cut out.
+    /// Horizontal walls reach over the corners and vertical walls stop short of them, so
+    /// no two walls overlap.
+    pub fn walls(&self, dungeon: &Dungeon<T>) -> Vec<Wall<T>> {
+        let thickness = self.wall_options.thickness;
+        let below = thickness / (T::ONE + T::ONE);
+        let above = thickness - below;
+        let mut walls = Vec::new();
+        let mut lines : Vec<Line<T>> = Vec::new();
+        let boundaries = dungeon.rooms().iter().flat_map(|room| room.rect().edges())
+            .chain(dungeon.hallways().iter().flat_map(|hallway| hallway.edges()));
+        for (start, end) in boundaries {
+            if (start.x() - end.x()).abs() <= T::TOLERANCE {
+                line(&mut lines, true, start.x()).edges.push(ordered(start.y(), end.y()));
+            } else if (start.y() - end.y()).abs() <= T::TOLERANCE {
+                line(&mut lines, false, start.y()).edges.push(ordered(start.x(), end.x()));
+            } else {
+                walls.push(self.diagonal(&start, &end));
+            }
+        }
+        for door in dungeon.doors() {
+            let (start, end) = (door.start(), door.end());
+            let vertical = (start.x() - end.x()).abs() <= T::TOLERANCE;
+            let (at, opening) = if vertical { (start.x(), ordered(start.y(), end.y())) } else { (start.y(), ordered(start.x(), end.x())) };
+            if let Some(line) = lines.iter_mut().find(|line| line.vertical == vertical && (line.at - at).abs() <= T::TOLERANCE) {
+                line.doors.push(opening);
+            }
+        }
+
+        let mut horizontal : Vec<(Rect<T>, WallKind)> = Vec::new();
+        for line in lines.iter().filter(|line| !line.vertical) {
+            for piece in pieces(line) {
+                let low = if piece.extend_low { piece.low - below } else { piece.low };
+                let high = if piece.extend_high { piece.high + above } else { piece.high };
+                horizontal.push((Rect::new(Point::new(low, line.at - below), Point::new(high, line.at + above)), piece.kind));
+            }
+        }
+        for line in lines.iter().filter(|line| line.vertical) {
+            let (left, right) = (line.at - below, line.at + above);
+            for piece in pieces(line) {
+                let mut spans = vec![(piece.low, piece.high)];
+                for (rect, _) in horizontal.iter().filter(|(rect, _)| rect.lower_left().x() < right && rect.upper_right().x() > left) {
+                    spans = subtract(spans, (rect.lower_left().y(), rect.upper_right().y()));
+                }
+                for (low, high) in spans {
+                    walls.push(Wall::from_rect(&Rect::new(Point::new(left, low), Point::new(right, high)), piece.kind));
+                }
+            }
+        }
+        walls.extend(horizontal.iter().map(|&(ref rect, kind)| Wall::from_rect(rect, kind)));
+        walls
+    }
+
+    /// A wall centered on a diagonal edge. Only corridors have those, and nothing lies on
+    /// their other side.
+    fn diagonal(&self, start: &Point<T>, end: &Point<T>) -> Wall<T> {
+        let (start, end) = (start.cast::<f64>(), end.cast::<f64>());
+        let (dx, dy) = (end.x() - start.x(), end.y() - start.y());
+        let length = (dx * dx + dy * dy).sqrt();
+        let half = self.wall_options.thickness.to_f64() / 2f64;
+        let (nx, ny) = (-dy / length * half, dx / length * half);
+        let corner = |point: &Point<f64>, side: f64| Point::new(T::from_f64(point.x() + nx * side), T::from_f64(point.y() + ny * side));
+        Wall::new(vec![corner(&start, 1f64), corner(&end, 1f64), corner(&end, -1f64), corner(&start, -1f64)], WallKind::Exterior)
+    }
+}
+
+fn ordered<T: Scalar>(a: T, b: T) -> (T, T) {
+    (a.min(b), a.max(b))
+}
+
+fn line<T: Scalar>(lines: &mut Vec<Line<T>>, vertical: bool, at: T) -> &mut Line<T> {
+    let idx = match lines.iter().position(|line| line.vertical == vertical && (line.at - at).abs() <= T::TOLERANCE) {
+        Some(idx) => idx,
+        None => {
+            lines.push(Line { vertical, at, edges: Vec::new(), doors: Vec::new() });
+            lines.len() - 1
+        }
+    };
+    &mut lines[idx]
+}
+
+/// The walls along a line. Stretches covered by the edges of two regions are interior,
+/// those covered once are exterior, and the doors are cut out of both.
+fn pieces<T: Scalar>(line: &Line<T>) -> Vec<Piece<T>> {
+    let mut stops : Vec<T> = line.edges.iter().flat_map(|&(low, high)| vec![low, high]).collect();
+    stops.sort_by(|a, b| a.partial_cmp(b).expect("wall coordinate is NaN"));
+    stops.dedup_by(|a, b| (*a - *b).abs() <= T::TOLERANCE);
+    let mut pieces : Vec<Piece<T>> = Vec::new();
+    for pair in stops.windows(2) {
+        let middle = (pair[0].to_f64() + pair[1].to_f64()) / 2f64;
+        let count = line.edges.iter().filter(|&&(low, high)| low.to_f64() < middle && high.to_f64() > middle).count();
+        if count == 0 {
+            continue;
+        }
+        let kind = if count > 1 { WallKind::Interior } else { WallKind::Exterior };
+        let joined = match pieces.last_mut() {
+            Some(last) if (last.high - pair[0]).abs() <= T::TOLERANCE => {
+                if last.kind == kind {
+                    last.high = pair[1];
+                    true
+                } else {
+                    last.extend_high = false;
+                    false
+                }
+            },
+            _ => false
+        };
+        if !joined {
+            let continues = pieces.last().is_some_and(|last| (last.high - pair[0]).abs() <= T::TOLERANCE);
+            pieces.push(Piece { low: pair[0], high: pair[1], kind, extend_low: !continues, extend_high: true });
+        }
+    }
+    for &(low, high) in &line.doors {
+        let mut cut = Vec::with_capacity(pieces.len() + 1);
+        for piece in pieces {
+            if high <= piece.low || low >= piece.high {
+                cut.push(piece);
+                continue;
+            }
+            if low - piece.low > T::TOLERANCE {
+                cut.push(Piece { low: piece.low, high: low, kind: piece.kind, extend_low: piece.extend_low, extend_high: false });
+            }
+            if piece.high - high > T::TOLERANCE {
+                cut.push(Piece { low: high, high: piece.high, kind: piece.kind, extend_low: false, extend_high: piece.extend_high });
+            }
+        }
+        pieces = cut;
+    }
+    pieces
+}
+
+/// What is left of `spans` once `cut` is taken out of them.
+fn subtract<T: Scalar>(spans: Vec<(T, T)>, cut: (T, T)) -> Vec<(T, T)> {
+    let mut left = Vec::with_capacity(spans.len() + 1);
+    for (low, high) in spans {
+        if cut.1 <= low || cut.0 >= high {
+            left.push((low, high));
+            continue;
+        }
+        if cut.0 - low > T::TOLERANCE {
+            left.push((low, cut.0));
+        }
+        if high - cut.1 > T::TOLERANCE {
+            left.push((cut.1, high));
+        }
+    }
+    left
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use room::{Room, RoomId};
+    use chunk::{Chunk, ChunkSplit};
+    use door::{Door, DoorKind, Region};
+
+    #[test]
+    fn test_walls_around_door() {
+        let mut dungeon = Dungeon::new();
+        dungeon.add_room(Room::new(Chunk::new(Point::new(0i32, 0i32), Point::new(10i32, 10i32), ChunkSplit::Vertical)));
+        dungeon.add_room(Room::new(Chunk::new(Point::new(10i32, 0i32), Point::new(20i32, 10i32), ChunkSplit::Vertical)));
+        dungeon.add_door(Door::new(Region::Room(RoomId::new(0)), Region::Room(RoomId::new(1)), Point::new(10i32, 4i32), Point::new(10i32, 6i32), DoorKind::Connectivity));
+        let walls = WallBuilder::new(&WallOptions::new(3i32)).walls(&dungeon);
+        assert_eq!(6, walls.len());
+        let interior : Vec<Rect<i32>> = walls.iter().filter(|wall| wall.kind() == WallKind::Interior).map(|wall| wall.bounds()).collect();
+        assert_eq!(2, interior.len());
+        assert!(interior.contains(&Rect::new(Point::new(9i32, 2i32), Point::new(12i32, 4i32))));
+        assert!(interior.contains(&Rect::new(Point::new(9i32, 6i32), Point::new(12i32, 9i32))));
+        assert!(walls.iter().any(|wall| wall.bounds() == Rect::new(Point::new(-1i32, -1i32), Point::new(22i32, 2i32))));
+        let area : i32 = walls.iter().map(|wall| wall.bounds().area()).sum();
+        assert_eq!(2 * 23 * 3 + 2 * 7 * 3 + 5 * 3, area);
+    }
+
+    #[test]
+    fn test_pieces() {
+        let line = Line { vertical: false, at: 0f32, edges: vec![(0f32, 10f32), (4f32, 8f32), (10f32, 12f32)], doors: vec![(5f32, 6f32)] };
+        let pieces : Vec<(f32, f32, WallKind, bool, bool)> = pieces(&line).iter()
+            .map(|piece| (piece.low, piece.high, piece.kind, piece.extend_low, piece.extend_high)).collect();
+        assert_eq!(vec![
+            (0f32, 4f32, WallKind::Exterior, true, false),
+            (4f32, 5f32, WallKind::Interior, false, false),
+            (6f32, 8f32, WallKind::Interior, false, false),
+            (8f32, 12f32, WallKind::Exterior, false, true),
+        ], pieces);
+    }
+}
@@ -0,0 +1,15 @@
+use scalar::Scalar;
+
+/// Walls are centered on the boundaries they stand on, so they take half their
+/// thickness off the rooms and hallways on either side.
+pub struct WallOptions<T = f32> {
+    pub thickness: T,
+}
+
+impl<T: Scalar> WallOptions<T> {
+    pub fn new(thickness: T) -> WallOptions<T> {
+        WallOptions {
+            thickness
+        }
+    }
+}
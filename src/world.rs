@@ -0,0 +1,201 @@
+use dungeonbuilder::DungeonBuilder;
+use worldoptions::WorldOptions;
+use sector::{Sector, Side};
+use point::Point;
+use rect::Rect;
+use scalar::Scalar;
+use rand::{Rng, SeedableRng, Isaac64Rng};
+use std::collections::HashMap;
+
+const SECTOR_KEY: i64 = 0;
+const VERTICAL_BORDER_KEY: i64 = 1;
+const HORIZONTAL_BORDER_KEY: i64 = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WorldError {
+    /// The sector is too small to hold the hallway ring and a gate on each border. It
+    /// must be more than four gate widths.
+    SectorTooSmall,
+}
+
+type Configure<T> = Box<dyn Fn(&mut DungeonBuilder<T>)>;
+
+/// An endless grid of sectors generated on demand. Every sector is built from a seed mixed
+/// from the world seed and its coordinates, so it comes out the same whenever and in
+/// whatever order it is generated.
+///
+/// Each sector is laid out as a hallway ring just inside its border, with the rooms
+/// generated inside the ring and one short hallway from the ring out to each border. Where
+/// that hallway meets the border is drawn from the seed of the border itself, which both
+/// sectors next to it share, so the hallways line up across the seam.
+pub struct World<T = f32> {
+    seed: u64,
+    world_options: WorldOptions<T>,
+    configure: Configure<T>,
+    sectors: HashMap<(i32, i32), Sector<T>>
+}
+
+impl<T: Scalar> World<T> {
+    /// `configure` sets the options of the builder used for every sector. The world
+    /// chooses the area and the seed itself, so `configure` should not call `in_area`.
+    pub fn new<F>(seed: u64, world_options: WorldOptions<T>, configure: F) -> Result<World<T>, WorldError>
+        where F: Fn(&mut DungeonBuilder<T>) + 'static {
+        let gate_width = world_options.gate_width;
+        if world_options.sector_size <= gate_width + gate_width + gate_width + gate_width {
+            return Err(WorldError::SectorTooSmall);
+        }
+        Ok(World { seed, world_options, configure: Box::new(configure), sectors: HashMap::new() })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The sector at the given coordinates, generating it the first time it is asked for.
+    pub fn sector(&mut self, x: i32, y: i32) -> &Sector<T> {
+        if !self.sectors.contains_key(&(x, y)) {
+            let sector = self.generate(x, y);
+            self.sectors.insert((x, y), sector);
+        }
+        &self.sectors[&(x, y)]
+    }
+
+    pub fn loaded(&self, x: i32, y: i32) -> Option<&Sector<T>> {
+        self.sectors.get(&(x, y))
+    }
+
+    /// Drops a generated sector. Asking for it again regenerates the same layout.
+    pub fn unload(&mut self, x: i32, y: i32) -> Option<Sector<T>> {
+        self.sectors.remove(&(x, y))
+    }
+
+    /// Generates the sector at the given coordinates without keeping it.
+    pub fn generate(&self, x: i32, y: i32) -> Sector<T> {
+        let size = self.world_options.sector_size;
+        let width = self.world_options.gate_width;
+        let lower_left = Point::new(T::from_f64(x as f64) * size, T::from_f64(y as f64) * size);
+        let corner = |dx: T, dy: T| lower_left + Point::new(dx, dy);
+        let ring = width + width;
+        let far = size - width;
+        let inner_far = size - ring;
+
+        let north = self.gate_offset(HORIZONTAL_BORDER_KEY, x, y + 1);
+        let east = self.gate_offset(VERTICAL_BORDER_KEY, x + 1, y);
+        let south = self.gate_offset(HORIZONTAL_BORDER_KEY, x, y);
+        let west = self.gate_offset(VERTICAL_BORDER_KEY, x, y);
+        let gates = [
+            (corner(north, size), corner(north + width, size)),
+            (corner(size, east), corner(size, east + width)),
+            (corner(south, T::ZERO), corner(south + width, T::ZERO)),
+            (corner(T::ZERO, west), corner(T::ZERO, west + width)),
+        ];
+
+        let mut builder = DungeonBuilder::new();
+        (self.configure)(&mut builder);
+        builder.with_seed(mix(self.seed, &[SECTOR_KEY, x as i64, y as i64]))
+            .in_area(corner(ring, ring), corner(inner_far, inner_far))
+            .with_hallway_area(corner(width, width), corner(far, ring))
+            .with_hallway_area(corner(width, inner_far), corner(far, far))
+            .with_hallway_area(corner(width, ring), corner(ring, inner_far))
+            .with_hallway_area(corner(inner_far, ring), corner(far, inner_far))
+            .with_hallway_area(corner(north, far), gates[Side::North as usize].1)
+            .with_hallway_area(corner(far, east), gates[Side::East as usize].1)
+            .with_hallway_area(gates[Side::South as usize].0, corner(south + width, width))
+            .with_hallway_area(gates[Side::West as usize].0, corner(width, west + width));
+        let dungeon = builder.build();
+        Sector::new(x, y, Rect::new(lower_left, corner(size, size)), gates, dungeon)
+    }
+
+    /// Where the gate on a border starts, measured from the lower or left end of the
+    /// border. The gate always opens onto the outer side of the hallway ring.
+    fn gate_offset(&self, border: i64, x: i32, y: i32) -> T {
+        let width = self.world_options.gate_width;
+        let mut rng = Isaac64Rng::from_seed(&[mix(self.seed, &[border, x as i64, y as i64])]);
+        rng.gen_range(width, self.world_options.sector_size - width - width)
+    }
+}
+
+/// SplitMix64 finalizer folded over `values`, used to derive sector and border seeds.
+fn mix(seed: u64, values: &[i64]) -> u64 {
+    values.iter().fold(seed, |hash, &value| {
+        let mut z = (hash ^ value as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use door::Region;
+    use dimensionoptions::DimensionOptions;
+    use hallwayoptions::HallwayOptions;
+    use connectivityoptions::ConnectivityOptions;
+
+    fn world(seed: u64) -> World {
+        World::new(seed, WorldOptions::new(200f32, 4f32), |builder: &mut DungeonBuilder| {
+            builder.with_dimension_options(DimensionOptions::new(10f32,10f32,100f32))
+                .with_hallway_options(HallwayOptions::new(15f32, 0f32, 3f32, 5f32))
+                .with_connectivity_options(ConnectivityOptions::new(2f32));
+        }).expect("sector is large enough")
+    }
+
+    #[test]
+    fn test_borders_match() {
+        let mut world = world(7);
+        let east = world.sector(-1, 2).gate(Side::East);
+        assert_eq!(east, world.sector(0, 2).gate(Side::West));
+        let north = world.sector(3, -1).gate(Side::North);
+        assert_eq!(north, world.sector(3, 0).gate(Side::South));
+        assert_eq!(Point::new(0f32, 0f32), *world.sector(0, 0).rect().lower_left());
+        assert_eq!(Point::new(-200f32, 400f32), *world.sector(-1, 2).rect().lower_left());
+    }
+
+    #[test]
+    fn test_gates_open_onto_hallways() {
+        let world = world(11);
+        let sector = world.generate(2, -3);
+        assert!(sector.dungeon().is_connected());
+        let inward = [Point::new(0f32, -1f32), Point::new(-1f32, 0f32), Point::new(0f32, 1f32), Point::new(1f32, 0f32)];
+        for (side, step) in [Side::North, Side::East, Side::South, Side::West].iter().zip(inward.iter()) {
+            let (start, end) = sector.gate(*side);
+            let inside = start.lerp(&end, 0.5f64) + *step;
+            assert!(sector.rect().contains(&start) && sector.rect().contains(&end));
+            match sector.dungeon().region_at(&inside) {
+                Some(Region::Hallway(_)) => {},
+                _ => panic!("gate on {:?} does not open onto a hallway", side)
+            }
+        }
+    }
+
+    #[test]
+    fn test_sectors_are_deterministic() {
+        let rooms = |sector: &Sector| sector.dungeon().rooms().iter().map(|room| *room.rect()).collect::<Vec<_>>();
+        let mut first = world(42);
+        first.sector(0, 0);
+        let expected = rooms(first.sector(5, -4));
+        assert_eq!(expected, rooms(&world(42).generate(5, -4)));
+        assert!(first.unload(5, -4).is_some());
+        assert!(first.loaded(5, -4).is_none());
+        assert_eq!(expected, rooms(first.sector(5, -4)));
+        assert!(expected != rooms(&world(43).generate(5, -4)));
+    }
+
+    #[test]
+    fn test_integer_world() {
+        let mut world = World::new(3, WorldOptions::new(120i32, 3i32), |builder: &mut DungeonBuilder<i32>| {
+            builder.with_dimension_options(DimensionOptions::new(8i32,8i32,64i32))
+                .with_hallway_options(HallwayOptions::new(10f32, 0i32, 2i32, 3i32));
+        }).expect("sector is large enough");
+        let east = world.sector(0, 0).gate(Side::East);
+        assert_eq!(east, world.sector(1, 0).gate(Side::West));
+        assert_eq!(3, east.1.y() - east.0.y());
+    }
+
+    #[test]
+    fn test_sector_too_small() {
+        let world = World::new(1, WorldOptions::new(16f32, 4f32), |_: &mut DungeonBuilder| {});
+        assert_eq!(Some(WorldError::SectorTooSmall), world.err());
+    }
+}
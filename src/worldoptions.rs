@@ -0,0 +1,15 @@
+use scalar::Scalar;
+
+pub struct WorldOptions<T = f32> {
+    pub sector_size: T,
+    pub gate_width: T,
+}
+
+impl<T: Scalar> WorldOptions<T> {
+    pub fn new(sector_size: T, gate_width: T) -> WorldOptions<T> {
+        WorldOptions { 
+            sector_size, 
+            gate_width 
+        }
+    }
+}